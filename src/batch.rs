@@ -0,0 +1,151 @@
+//! Helpers for running many requests of the same type concurrently.
+
+use futures_util::stream::{self, StreamExt};
+
+use crate::client::{ApiResponse, Executor};
+use crate::error::{BuilderError, Error};
+
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// A set of requests that are executed concurrently, such as the lookups created by
+/// [`GlobalEnvsBuilder::get_many`](crate::builders::envs::get::GlobalEnvsBuilder::get_many).
+///
+/// At most [`concurrency`](Self::concurrency) requests are in flight at once. Results are returned
+/// in the same order as the requests, and each request succeeds or fails on its own, including
+/// requests whose ids failed validation.
+#[derive(Clone)]
+pub struct Batch<B> {
+    builders: Vec<Result<B, BuilderError>>,
+    concurrency: usize,
+}
+
+impl<B> Batch<B> {
+    pub(crate) fn new(builders: Vec<Result<B, BuilderError>>) -> Self {
+        Self {
+            builders,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Creates a batch with one request per id, built by calling `get` on a copy of `parent`.
+    pub(crate) fn from_ids<P, I, F>(parent: &P, ids: I, get: F) -> Self
+    where
+        P: Clone,
+        I: IntoIterator,
+        F: Fn(P, I::Item) -> Result<B, BuilderError>,
+    {
+        Self::new(ids.into_iter().map(|id| get(parent.clone(), id)).collect())
+    }
+
+    /// Sets the maximum number of requests in flight at once. Defaults to 8.
+    pub fn concurrency(mut self, n: usize) -> Self {
+        self.concurrency = n.max(1);
+        self
+    }
+}
+
+impl<B: Executor> Batch<B> {
+    /// Executes every request and returns their results in order.
+    pub async fn execute(&self) -> Vec<Result<ApiResponse<B::T>, Error>> {
+        stream::iter(&self.builders)
+            .map(|b| async move {
+                match b {
+                    Ok(b) => b.execute().await,
+                    Err(err) => Err(Error::Builder(err.clone())),
+                }
+            })
+            .buffered(self.concurrency)
+            .collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use http::{Request, Response};
+
+    use crate::transport::{Bytes, Transport};
+    use crate::Coder;
+
+    /// Serves users by id, answering later ids sooner so responses complete out of order.
+    #[derive(Default)]
+    struct UserTransport {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for UserTransport {
+        async fn send(&self, req: Request<Bytes>) -> Result<Response<Bytes>, crate::Error> {
+            let n = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(n, Ordering::SeqCst);
+
+            let id = req.uri().path().rsplit('/').next().unwrap().to_string();
+            let n = id[id.len() - 1..].parse::<u64>().unwrap();
+            let delay = 40 - 10 * n.min(4);
+            tokio::time::delay_for(Duration::from_millis(delay)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            if n == 0 {
+                return Ok(Response::builder()
+                    .status(404)
+                    .body(r#"{"error":{"msg":"user not found"}}"#.into())?);
+            }
+            let body = format!(
+                r#"{{
+                    "id": "{}",
+                    "name": "",
+                    "username": "{}",
+                    "email": "",
+                    "dotfiles_git_uri": "",
+                    "roles": [],
+                    "avatar_hash": "",
+                    "key_regenerated_at": "2020-04-03T17:05:56.964782Z",
+                    "created_at": "2020-04-03T17:05:56.964782Z",
+                    "updated_at": "2020-05-29T18:10:33.532351Z"
+                }}"#,
+                id, id
+            );
+            Ok(Response::new(body.into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_many() {
+        let t = Arc::new(UserTransport::default());
+        let c =
+            Coder::with_transport("https://coder.example.com".into(), "key", t.clone()).unwrap();
+
+        let ids = vec![
+            "5e876cf4-000000000000000000000001",
+            "5e876cf4-000000000000000000000002",
+            "5e876cf4-000000000000000000000000",
+            "not an id",
+            "5e876cf4-000000000000000000000003",
+            "5e876cf4-000000000000000000000004",
+        ];
+        let res = c
+            .users()
+            .get_many(ids.clone())
+            .concurrency(2)
+            .execute()
+            .await;
+
+        assert_eq!(res.len(), ids.len());
+        for (id, res) in ids.into_iter().zip(res) {
+            match id {
+                "not an id" => assert!(matches!(res, Err(crate::Error::Builder(_)))),
+                "5e876cf4-000000000000000000000000" => {
+                    assert_eq!(res.expect("send request").status_code, 404)
+                }
+                id => assert_eq!(res.expect("send request").response.unwrap().id, id),
+            }
+        }
+        assert_eq!(t.max_in_flight.load(Ordering::SeqCst), 2);
+    }
+}
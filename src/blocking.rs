@@ -0,0 +1,146 @@
+//! A blocking API for executing requests, for use in synchronous code such as scripts and build
+//! helpers.
+//!
+//! Requests are run on a runtime managed by this crate, so no async runtime is needed. Blocking
+//! calls also work from inside an existing async context, but will block the calling thread until
+//! the request finishes.
+//!
+//! ```rust,no_run
+//! use coder::blocking::Executor;
+//! use coder::Coder;
+//!
+//! let c = Coder::new("https://coder.example.com".into(), "api key")?;
+//! let res = c.users().me().execute()?;
+//! # Ok::<(), coder::Error>(())
+//! ```
+
+use std::future::Future;
+use std::sync::mpsc;
+use std::sync::OnceLock;
+use std::thread;
+
+use serde::de::DeserializeOwned;
+use tokio::runtime::Handle;
+
+use crate::client::{ApiResponse, RawResponse};
+use crate::error::Error;
+
+/// Synchronous version of [`coder::Executor`](crate::Executor), implemented for every request
+/// builder.
+pub trait Executor {
+    type T: DeserializeOwned;
+
+    fn execute(&self) -> Result<ApiResponse<Self::T>, Error>;
+
+    fn execute_raw(&self) -> Result<RawResponse, Error>;
+
+    fn execute_as<U: DeserializeOwned + Send + 'static>(&self) -> Result<ApiResponse<U>, Error>;
+
+    fn execute_value(&self) -> Result<ApiResponse<serde_json::Value>, Error>;
+}
+
+impl<E> Executor for E
+where
+    E: crate::client::Executor + Clone + 'static,
+    E::T: Send + 'static,
+{
+    type T = E::T;
+
+    fn execute(&self) -> Result<ApiResponse<Self::T>, Error> {
+        let b = self.clone();
+        block_on(async move { crate::client::Executor::execute(&b).await })
+    }
+
+    fn execute_raw(&self) -> Result<RawResponse, Error> {
+        let b = self.clone();
+        block_on(async move { crate::client::Executor::execute_raw(&b).await })
+    }
+
+    fn execute_as<U: DeserializeOwned + Send + 'static>(&self) -> Result<ApiResponse<U>, Error> {
+        let b = self.clone();
+        block_on(async move { crate::client::Executor::execute_as(&b).await })
+    }
+
+    fn execute_value(&self) -> Result<ApiResponse<serde_json::Value>, Error> {
+        let b = self.clone();
+        block_on(async move { crate::client::Executor::execute_value(&b).await })
+    }
+}
+
+/// Runs a request on the blocking runtime and waits for its result.
+fn block_on<T, F>(fut: F) -> Result<T, Error>
+where
+    F: Future<Output = Result<T, Error>> + Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    runtime().spawn(async move {
+        // The receiver only goes away if the calling thread does.
+        let _ = tx.send(fut.await);
+    });
+
+    rx.recv().map_err(|_| Error::RuntimeShutdown)?
+}
+
+/// Returns a handle to the runtime used by blocking requests, starting it on first use. The
+/// runtime runs on its own thread for the rest of the process, so it never conflicts with a
+/// runtime the caller may already be inside of.
+fn runtime() -> &'static Handle {
+    static HANDLE: OnceLock<Handle> = OnceLock::new();
+
+    HANDLE.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        thread::Builder::new()
+            .name("coder-blocking".into())
+            .spawn(move || {
+                let mut rt = tokio::runtime::Builder::new()
+                    .basic_scheduler()
+                    .enable_all()
+                    .build()
+                    .expect("build coder blocking runtime");
+                tx.send(rt.handle().clone()).unwrap();
+                // Keep the runtime alive for the rest of the process.
+                rt.block_on(std::future::pending::<()>());
+            })
+            .expect("spawn coder blocking runtime thread");
+
+        rx.recv().expect("start coder blocking runtime")
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::Executor;
+    use crate::client::test::{client, ids::*};
+
+    #[test]
+    fn test_blocking_user() {
+        let c = client();
+
+        let res = c
+            .users()
+            .get(USER_ID)
+            .expect("build request")
+            .execute()
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        assert_eq!(res.id, USER_ID);
+    }
+
+    #[tokio::test]
+    async fn test_blocking_inside_runtime() {
+        let c = client();
+
+        let res = c
+            .users()
+            .me()
+            .execute()
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        assert!(!res.id.is_empty());
+    }
+}
@@ -1,18 +1,323 @@
-use hyper::{Body, Request};
+use hyper::Request;
+use serde::Serialize;
 use std::collections::HashMap;
 use url::Url;
 
+use crate::client::Route;
+use crate::error::BuilderError;
+use crate::transport::Bytes;
+
 pub(crate) struct Builder {
     pub query: HashMap<&'static str, String>,
     pub url: Url,
-    pub req: Request<Body>,
+    pub route: String,
+    pub req: Request<Bytes>,
+}
+
+impl Clone for Builder {
+    fn clone(&self) -> Self {
+        Self {
+            query: self.query.clone(),
+            url: self.url.clone(),
+            route: self.route.clone(),
+            req: crate::middleware::clone_request(&self.req),
+        }
+    }
 }
 
 impl Builder {
+    pub(crate) fn new(url: Url, req: Request<Bytes>) -> Self {
+        Self {
+            query: HashMap::new(),
+            url,
+            route: String::new(),
+            req,
+        }
+    }
+
+    /// Appends path segments to the URL, and route segments to the route template. Segments are
+    /// percent-encoded, so they should be validated with [`validate`] first to rule out segments
+    /// such as `..` that would change the route.
+    ///
+    /// Returns an error if the URL can't have path segments. `Coder` rejects such base URLs, and
+    /// [`build`](Self::build) returns the same error, so callers that can't fail may ignore it.
+    pub(crate) fn join_path(&mut self, path: &[&str], route: &[&str]) -> Result<(), BuilderError> {
+        let extended = self.url.path_segments_mut().map(|mut segments| {
+            segments.extend(path);
+        });
+        extended.map_err(|()| BuilderError::InvalidUri(self.url.to_string()))?;
+        for seg in route {
+            self.route.push('/');
+            self.route.push_str(seg);
+        }
+        Ok(())
+    }
+
+    /// Sets a query parameter. Values are percent-encoded, so any string is accepted.
+    pub(crate) fn join_query(&mut self, key: &'static str, value: String) {
+        self.query.insert(key, value);
+    }
+
+    pub(crate) fn join_body<B: Serialize + ?Sized>(
+        &mut self,
+        body: &B,
+    ) -> Result<(), BuilderError> {
+        let body = serde_json::to_vec(body).map_err(|err| BuilderError::Body(err.to_string()))?;
+        self.req.headers_mut().insert(
+            hyper::header::CONTENT_TYPE,
+            hyper::header::HeaderValue::from_static("application/json"),
+        );
+        *self.req.body_mut() = body.into();
+        Ok(())
+    }
+
     #[inline]
-    pub(crate) fn build(mut self) -> Request<Body> {
-        self.url.query_pairs_mut().extend_pairs(self.query);
-        *self.req.uri_mut() = self.url.to_string().parse().unwrap();
-        self.req
+    pub(crate) fn build(&self) -> Result<Request<Bytes>, BuilderError> {
+        if self.url.cannot_be_a_base() {
+            return Err(BuilderError::InvalidUri(self.url.to_string()));
+        }
+        let mut url = self.url.clone();
+        url.query_pairs_mut().extend_pairs(&self.query);
+        let mut req = crate::middleware::clone_request(&self.req);
+        *req.uri_mut() = url
+            .as_str()
+            .parse()
+            .map_err(|_| BuilderError::InvalidUri(url.to_string()))?;
+        req.extensions_mut().insert(Route(self.route.clone()));
+        Ok(req)
+    }
+}
+
+/// Implemented for the types of route variables in `impl_builder!`, so each is validated before
+/// it's added to a route.
+pub(crate) trait RouteVar {
+    fn validate_route_var(&self, name: &'static str) -> Result<(), BuilderError>;
+}
+
+/// Route variables without an id type, such as image tags, only need to be valid path segments.
+impl RouteVar for String {
+    fn validate_route_var(&self, name: &'static str) -> Result<(), BuilderError> {
+        validate::segment(name, self)
+    }
+}
+
+/// Validation rules for route variables.
+pub(crate) mod validate {
+    use crate::error::BuilderError;
+
+    const MAX_LEN: usize = 256;
+    /// Values longer than this are truncated in errors.
+    const MAX_ERROR_LEN: usize = 64;
+
+    fn invalid(kind: &'static str, value: &str, reason: &'static str) -> BuilderError {
+        let value = match value.char_indices().nth(MAX_ERROR_LEN) {
+            Some((i, _)) => format!("{}...", &value[..i]),
+            None => value.to_string(),
+        };
+        BuilderError::InvalidId {
+            kind,
+            value,
+            reason,
+        }
+    }
+
+    /// Accepts any value that maps to exactly one path segment of the intended route.
+    pub(crate) fn segment(kind: &'static str, value: &str) -> Result<(), BuilderError> {
+        if value.is_empty() {
+            return Err(invalid(kind, value, "must not be empty"));
+        }
+        if value.len() > MAX_LEN {
+            return Err(invalid(kind, value, "is too long"));
+        }
+        if value == "." || value == ".." {
+            return Err(invalid(kind, value, "must not be a relative path segment"));
+        }
+        if value.contains(['/', '\\']) {
+            return Err(invalid(kind, value, "must not contain path separators"));
+        }
+        if value.contains(|c: char| c.is_control() || c.is_whitespace()) {
+            return Err(invalid(
+                kind,
+                value,
+                "must not contain whitespace or control characters",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Accepts ids generated by the manager, such as `5e876cf4-10abe9b2e54eb609c5ec1870`.
+    pub(crate) fn object_id(kind: &'static str, value: &str) -> Result<(), BuilderError> {
+        segment(kind, value)?;
+        let ok = value.len() == 33
+            && value.char_indices().all(|(i, c)| match i {
+                8 => c == '-',
+                _ => c.is_ascii_hexdigit(),
+            });
+        if !ok {
+            return Err(invalid(
+                kind,
+                value,
+                "must be an object id such as 5e876cf4-10abe9b2e54eb609c5ec1870",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Builder;
+    use crate::client::Executor;
+    use crate::error::{BuilderError, Error};
+    use crate::transport::test::MockTransport;
+    use crate::{Coder, Method};
+    use hyper::Request;
+
+    const USER_ID: &str = "5e876cf4-10abe9b2e54eb609c5ec1870";
+
+    fn mock() -> (Coder, std::sync::Arc<MockTransport>) {
+        let t = MockTransport::new(200, "null");
+        let c =
+            Coder::with_transport("https://coder.example.com".into(), "key", t.clone()).unwrap();
+        (c, t)
+    }
+
+    #[test]
+    fn test_hostile_ids() {
+        let (c, t) = mock();
+
+        let long = "a".repeat(300);
+        for id in &[
+            "",
+            ".",
+            "..",
+            "a/b",
+            "..%2f..",
+            "a\\b",
+            "a\nb",
+            " ",
+            "5e876cf4-10abe9b2e54eb609c5ec187z",
+            "5e876cf4_10abe9b2e54eb609c5ec1870",
+            "5e876cf4-10abe9b2e54eb609c5ec18é",
+            &long,
+        ] {
+            let err = c.users().get(*id).err().expect(id);
+            assert!(matches!(
+                err,
+                BuilderError::InvalidId { kind: "UserId", .. }
+            ));
+        }
+        assert!(c.users().get(USER_ID).is_ok());
+
+        // Tags aren't object ids, but still have to be a single path segment.
+        let images = c.images().get("5ea8a569-596e6afd9301c23f8dabd87c").unwrap();
+        assert!(images.clone().tags().get("v1.0-rc1").is_ok());
+        for tag in &["latest tag", "..", "a/b"] {
+            let err = images.clone().tags().get(*tag).err().expect(tag);
+            assert!(matches!(err, BuilderError::InvalidId { kind: "tag", .. }));
+        }
+
+        assert!(t.requests.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_org_and_secret_ids() {
+        let (c, _) = mock();
+
+        assert!(c.orgs().get("default").is_ok());
+        assert!(c.orgs().get("../users").is_err());
+
+        let secrets = c.users().me().secrets();
+        assert!(secrets.clone().get(USER_ID).is_ok());
+        assert!(secrets.clone().get("my-secret").is_ok());
+        let err = secrets.get("../keys").err().unwrap();
+        assert!(matches!(
+            err,
+            BuilderError::InvalidId {
+                kind: "SecretId",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_join_path_without_base() {
+        let url = "mailto:admin@coder.example.com".parse().unwrap();
+        let mut b = Builder::new(url, Request::new(Default::default()));
+
+        let err = b.join_path(&["users"], &["users"]).unwrap_err();
+        assert!(matches!(err, BuilderError::InvalidUri(_)));
+        assert_eq!(b.build().err(), Some(err));
+    }
+
+    #[test]
+    fn test_invalid_id_error() {
+        let (c, _) = mock();
+
+        let err = c.users().get("a".repeat(300)).err().unwrap();
+        match &err {
+            BuilderError::InvalidId { value, reason, .. } => {
+                assert_eq!(value.len(), 67);
+                assert_eq!(*reason, "is too long");
+            }
+            err => panic!("unexpected error {:?}", err),
+        }
+        assert!(err.to_string().starts_with("invalid UserId \"aaaa"));
+    }
+
+    #[tokio::test]
+    async fn test_escaping() {
+        let (c, t) = mock();
+
+        c.request(Method::GET, "/users/a%2F..%2Fb")
+            .unwrap()
+            .query("q", "a&b=c#d e")
+            .send_raw()
+            .await
+            .unwrap();
+
+        let reqs = t.requests.lock().unwrap();
+        assert_eq!(
+            reqs[0].uri(),
+            "https://coder.example.com/api/users/a%252F..%252Fb?q=a%26b%3Dc%23d+e"
+        );
+    }
+
+    #[test]
+    fn test_raw_path_traversal() {
+        let (c, _) = mock();
+
+        for path in &["/users/../orgs", "/users/./me", "/users/a\\b"] {
+            let res = c.request(Method::GET, path);
+            assert!(matches!(res, Err(Error::Builder(_))), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_invalid_base_url() {
+        for url in &[
+            "mailto:user@example.com",
+            "data:text/plain,x",
+            "ftp://example.com",
+        ] {
+            let t = MockTransport::new(200, "null");
+            let res = Coder::with_transport(url.to_string(), "key", t);
+            assert!(matches!(res, Err(Error::InvalidBaseUrl { .. })), "{}", url);
+        }
+
+        let t = MockTransport::new(200, "null");
+        assert!(Coder::with_transport("not a url".into(), "key", t).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_valid_requests_are_sent() {
+        let (c, t) = mock();
+
+        c.users().get(USER_ID).unwrap().execute_raw().await.unwrap();
+        let reqs = t.requests.lock().unwrap();
+        assert_eq!(
+            reqs[0].uri(),
+            "https://coder.example.com/api/users/5e876cf4-10abe9b2e54eb609c5ec1870"
+        );
     }
 }
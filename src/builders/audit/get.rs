@@ -0,0 +1,101 @@
+imports!();
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+new_builder!(
+    /// `/api/audit`
+    AuditLogs,
+);
+
+exec!(
+    AuditLogs -> Vec<crate::models::AuditEvent>,
+);
+
+impl_client!(
+    /// Begins an audit log query. Must be a site admin or site auditor.
+    -> audit ["audit"] -> AuditLogs,
+);
+
+impl_builder!(
+    @AuditLogs
+        /// Only return events performed by the user with the given id.
+        ?> actor ["actor_id"] -> id: crate::models::UserId,
+        /// Only return events on the given type of resource.
+        ?> resource_type ["resource_type"] -> t: crate::models::AuditResourceType,
+        /// Only return events for the given action.
+        ?> action ["action"] -> a: crate::models::AuditAction,
+        /// The maximum number of events to return.
+        ?> limit ["limit"] -> n: usize,
+        /// The number of events to skip, used for paging through results.
+        ?> offset ["offset"] -> n: usize,
+);
+
+impl AuditLogsBuilder {
+    /// Only return events that happened at or after the given time.
+    pub fn since(mut self, t: DateTime<Utc>) -> Self {
+        join_query!(
+            self,
+            "start",
+            t.to_rfc3339_opts(SecondsFormat::Millis, true)
+        );
+        self
+    }
+
+    /// Only return events that happened before the given time.
+    pub fn until(mut self, t: DateTime<Utc>) -> Self {
+        join_query!(self, "end", t.to_rfc3339_opts(SecondsFormat::Millis, true));
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::client::test::client;
+    use crate::client::Executor;
+    use crate::models::AuditResourceType;
+
+    #[tokio::test]
+    async fn test_audit_logs() {
+        let c = client();
+
+        let res = c
+            .audit()
+            .limit(10)
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        assert!(res.len() <= 10);
+
+        // they should all have non-empty ids
+        let ok = res.iter().all(|ev| !ev.id.is_empty());
+        assert!(ok);
+    }
+
+    #[tokio::test]
+    async fn test_audit_logs_filtered() {
+        let c = client();
+        let until = chrono::Utc::now();
+        let since = until - chrono::Duration::days(30);
+
+        let res = c
+            .audit()
+            .resource_type(AuditResourceType::Environment)
+            .since(since)
+            .until(until)
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        let ok = res.iter().all(|ev| {
+            ev.resource_type == AuditResourceType::Environment
+                && ev.time >= since
+                && ev.time < until
+        });
+        assert!(ok);
+    }
+}
@@ -0,0 +1,111 @@
+imports!();
+
+new_builder!(
+    /// `POST /api/environments/:id/devurls`
+    CreateDevUrl,
+);
+
+use crate::builders::devurls::get::DevUrlsBuilder;
+
+exec!(
+    CreateDevUrl -> () = POST,
+);
+
+from!(
+    @DevUrls
+        -> CreateDevUrl,
+);
+
+impl_builder!(
+    @DevUrls
+        /// Creates a new dev URL for the environment.
+        +> create [] -> CreateDevUrl (req: crate::models::DevUrlRequest),
+);
+
+#[cfg(test)]
+mod test {
+    use crate::client::test::{client, ids::*};
+    use crate::client::Executor;
+    use crate::models::{DevUrlAccess, DevUrlRequest};
+
+    #[tokio::test]
+    async fn test_devurl_lifecycle() {
+        let c = client();
+        let name = "coderrstest";
+
+        let mut req = DevUrlRequest {
+            port: 8123,
+            name: name.into(),
+            access: DevUrlAccess::Private,
+        };
+
+        c.envs()
+            .get(ENV_ID)
+            .expect("build request")
+            .devurls()
+            .create(&req)
+            .expect("build request")
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        let urls = c
+            .envs()
+            .get(ENV_ID)
+            .expect("build request")
+            .devurls()
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        let url = urls
+            .into_iter()
+            .find(|url| url.name == name)
+            .expect("created dev url should be listed");
+        assert_eq!(url.port, req.port);
+        assert_eq!(url.access, DevUrlAccess::Private);
+
+        req.access = DevUrlAccess::Org;
+        c.envs()
+            .get(ENV_ID)
+            .expect("build request")
+            .devurls()
+            .update(&url.id, &req)
+            .expect("build request")
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        c.envs()
+            .get(ENV_ID)
+            .expect("build request")
+            .devurls()
+            .delete(&url.id)
+            .expect("build request")
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        let urls = c
+            .envs()
+            .get(ENV_ID)
+            .expect("build request")
+            .devurls()
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        // the dev url should be gone
+        assert!(urls.iter().all(|u| u.id != url.id));
+    }
+}
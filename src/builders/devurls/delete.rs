@@ -0,0 +1,23 @@
+imports!();
+
+new_builder!(
+    /// `DELETE /api/environments/:id/devurls/:id`
+    DeleteDevUrl,
+);
+
+use crate::builders::devurls::get::DevUrlsBuilder;
+
+exec!(
+    DeleteDevUrl -> () = DELETE,
+);
+
+from!(
+    @DevUrls
+        -> DeleteDevUrl,
+);
+
+impl_builder!(
+    @DevUrls
+        /// Deletes a dev URL by its id.
+        => delete [] -> DeleteDevUrl = id: DevUrlId,
+);
@@ -0,0 +1,49 @@
+imports!();
+
+new_builder!(
+    /// `/api/environments/:id/devurls`
+    DevUrls,
+);
+
+use crate::builders::envs::get::GlobalEnvBuilder;
+
+exec!(
+    DevUrls -> Vec<crate::models::DevUrl>,
+);
+
+from!(
+    @GlobalEnv
+        -> DevUrls,
+);
+
+impl_builder!(
+    @GlobalEnv
+        /// Queries all dev URLs belonging to the environment.
+        -> devurls ["devurls"] -> DevUrls,
+);
+
+#[cfg(test)]
+mod test {
+    use crate::client::test::{client, ids::*};
+    use crate::client::Executor;
+
+    #[tokio::test]
+    async fn test_env_devurls() {
+        let c = client();
+
+        let res = c
+            .envs()
+            .get(ENV_ID)
+            .expect("build request")
+            .devurls()
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        // they should all have non-empty ids
+        let ok = res.iter().all(|url| !url.id.is_empty());
+        assert!(ok);
+    }
+}
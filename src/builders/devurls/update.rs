@@ -0,0 +1,23 @@
+imports!();
+
+new_builder!(
+    /// `PUT /api/environments/:id/devurls/:id`
+    UpdateDevUrl,
+);
+
+use crate::builders::devurls::get::DevUrlsBuilder;
+
+exec!(
+    UpdateDevUrl -> () = PUT,
+);
+
+from!(
+    @DevUrls
+        -> UpdateDevUrl,
+);
+
+impl_builder!(
+    @DevUrls
+        /// Updates the port, name and access level of a dev URL by its id.
+        #> update [] -> UpdateDevUrl = id: DevUrlId (req: crate::models::DevUrlRequest),
+);
@@ -1,5 +1,7 @@
 imports!();
 
+use crate::batch::Batch;
+
 new_builder!(
     /// `/api/environments/:id`
     GlobalEnv,
@@ -47,9 +49,21 @@ impl_builder!(
     @GlobalEnvs
         /// Queries an environment by its id. Must be a site admin or a manager of the organization
         /// the environment belongs to.
-        => get [] -> GlobalEnv = id,
+        => get [] -> GlobalEnv = id: EnvId,
 );
 
+impl GlobalEnvsBuilder {
+    /// Queries many environments by their ids concurrently, returning a result for each id in
+    /// order. Each environment is requested separately.
+    pub fn get_many<I>(self, ids: I) -> Batch<GlobalEnvBuilder>
+    where
+        I: IntoIterator,
+        I::Item: Into<crate::models::EnvId>,
+    {
+        Batch::from_ids(&self, ids, Self::get)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::client::test::{client, ids::*};
@@ -62,6 +76,7 @@ mod test {
         let res = c
             .envs()
             .get(ENV_ID)
+            .expect("build request")
             .execute()
             .await
             .expect("send request")
@@ -82,6 +97,7 @@ mod test {
             let res = c
                 .orgs()
                 .get(ORG_ID)
+                .expect("build request")
                 .envs()
                 .execute()
                 .await
@@ -93,8 +109,8 @@ mod test {
             assert_ne!(res.len(), 0);
 
             // they should all have non-empty ids
-            let ok = res.iter().fold(false, |ok, env| ok || env.id != "");
-            assert_eq!(ok, true);
+            let ok = res.iter().any(|env| !env.id.as_str().is_empty());
+            assert!(ok);
         }
     }
 
@@ -108,8 +124,10 @@ mod test {
             let res = c
                 .orgs()
                 .get(ORG_ID)
+                .expect("build request")
                 .members()
                 .get(MEMBER_ID)
+                .expect("build request")
                 .envs()
                 .execute()
                 .await
@@ -121,8 +139,8 @@ mod test {
             assert_ne!(res.len(), 0);
 
             // they should all have non-empty ids
-            let ok = res.iter().fold(false, |ok, env| ok || env.id != "");
-            assert_eq!(ok, true);
+            let ok = res.iter().any(|env| !env.id.as_str().is_empty());
+            assert!(ok);
         }
     }
 }
@@ -45,8 +45,10 @@ mod test {
             let res = c
                 .images()
                 .get(IMAGE_ID)
+                .expect("build request")
                 .tags()
                 .get(IMAGE_TAG_ID)
+                .expect("build request")
                 .execute()
                 .await
                 .expect("send request")
@@ -64,6 +66,7 @@ mod test {
             let res = c
                 .images()
                 .get(IMAGE_ID)
+                .expect("build request")
                 .tags()
                 .execute()
                 .await
@@ -75,8 +78,8 @@ mod test {
             assert_ne!(res.len(), 0);
 
             // they should all have non-empty ids
-            let ok = res.iter().fold(false, |ok, img| ok || img.tag != "");
-            assert_eq!(ok, true);
+            let ok = res.iter().any(|img| !img.tag.is_empty());
+            assert!(ok);
         }
     }
 }
@@ -34,7 +34,7 @@ impl_builder!(
         -> images ["images"] -> OrgImages,
     @GlobalImages
         /// Queries an image in by its id.
-        => get [] -> GlobalImage = id,
+        => get [] -> GlobalImage = id: ImageId,
         /// Option to return the `environments` field on the `Image` struct.
         ?> with_envs ["envs"] -> v: bool,
         /// Option to return the `user_ids` field on the `Image` struct.
@@ -63,6 +63,7 @@ mod test {
         let res = c
             .images()
             .get(IMAGE_ID)
+            .expect("build request")
             .execute()
             .await
             .expect("send request")
@@ -79,6 +80,7 @@ mod test {
         let res = c
             .images()
             .get(IMAGE_ID)
+            .expect("build request")
             .with_envs(true)
             .execute()
             .await
@@ -92,6 +94,7 @@ mod test {
         let res = c
             .images()
             .get(IMAGE_ID)
+            .expect("build request")
             .with_envs(false)
             .execute()
             .await
@@ -110,6 +113,7 @@ mod test {
         let res = c
             .images()
             .get(IMAGE_ID)
+            .expect("build request")
             .with_user_ids(true)
             .execute()
             .await
@@ -123,6 +127,7 @@ mod test {
         let res = c
             .images()
             .get(IMAGE_ID)
+            .expect("build request")
             .with_user_ids(false)
             .execute()
             .await
@@ -144,6 +149,7 @@ mod test {
             let res = c
                 .orgs()
                 .get(ORG_ID)
+                .expect("build request")
                 .images()
                 .execute()
                 .await
@@ -151,8 +157,8 @@ mod test {
                 .response
                 .expect("api error returned");
 
-            assert!(res.len() > 0);
-            assert!(res.iter().fold(false, |ok, img| ok || img.id != "".into()));
+            assert!(!res.is_empty());
+            assert!(res.iter().any(|img| !img.id.as_str().is_empty()));
         }
     }
 }
@@ -1,7 +1,11 @@
+pub mod audit;
+pub mod devurls;
 pub mod envs;
 pub mod image_tags;
 pub mod images;
 pub mod orgs;
 pub mod registries;
+pub mod secrets;
 pub mod services;
+pub mod ssh_keys;
 pub mod users;
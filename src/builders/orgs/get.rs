@@ -41,7 +41,7 @@ impl_client!(
 impl_builder!(
     @Orgs
         /// Queries an organization by its id.
-        => get [] -> Org = id,
+        => get [] -> Org = id: OrgId,
         /// Queries the available namespaces for organizations.
         -> namespaces ["namespaces"] -> OrgNamespaces,
     @Org
@@ -49,7 +49,7 @@ impl_builder!(
         -> members ["members"] -> Members,
     @Members
         /// Queries a specific member in an organization by their user id.
-        => get [] -> Member = user_id,
+        => get [] -> Member = user_id: UserId,
 );
 
 #[cfg(test)]
@@ -73,8 +73,8 @@ mod test {
         assert_ne!(res.len(), 0);
 
         // they should all have non-empty ids
-        let ok = res.iter().fold(false, |ok, org| ok || org.id != "");
-        assert_eq!(ok, true);
+        let ok = res.iter().any(|org| !org.id.as_str().is_empty());
+        assert!(ok);
     }
 
     #[tokio::test]
@@ -84,6 +84,7 @@ mod test {
         let res = c
             .orgs()
             .get(ORG_ID)
+            .expect("build request")
             .execute()
             .await
             .expect("send request")
@@ -111,8 +112,8 @@ mod test {
         assert_ne!(res.len(), 0);
 
         // they should all be a non-empty string
-        let ok = res.iter().fold(false, |ok, n| ok || n != "");
-        assert_eq!(ok, true);
+        let ok = res.iter().any(|n| !n.is_empty());
+        assert!(ok);
     }
 
     mod members {
@@ -125,6 +126,7 @@ mod test {
             let res = c
                 .orgs()
                 .get(ORG_ID)
+                .expect("build request")
                 .members()
                 .execute()
                 .await
@@ -136,8 +138,8 @@ mod test {
             assert_ne!(res.len(), 0);
 
             // they should all have non-empty ids
-            let ok = res.iter().fold(false, |ok, mem| ok || mem.user.id != "");
-            assert_eq!(ok, true);
+            let ok = res.iter().any(|mem| !mem.user.id.as_str().is_empty());
+            assert!(ok);
         }
 
         #[tokio::test]
@@ -147,8 +149,10 @@ mod test {
             let res = c
                 .orgs()
                 .get(ORG_ID)
+                .expect("build request")
                 .members()
                 .get(MEMBER_ID)
+                .expect("build request")
                 .execute()
                 .await
                 .expect("send request")
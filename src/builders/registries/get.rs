@@ -36,7 +36,7 @@ impl_builder!(
         -> registries ["registries"] -> OrgRegistries,
     @GlobalRegistries
         /// Queries a specific registry in an organization by its id.
-        => get [] -> GlobalRegistry = id,
+        => get [] -> GlobalRegistry = id: RegistryId,
 );
 
 #[cfg(test)]
@@ -51,6 +51,7 @@ mod test {
         let res = c
             .registries()
             .get(REG_ID)
+            .expect("build request")
             .execute()
             .await
             .expect("send request")
@@ -71,6 +72,7 @@ mod test {
             let res = c
                 .orgs()
                 .get(ORG_ID)
+                .expect("build request")
                 .registries()
                 .execute()
                 .await
@@ -82,8 +84,8 @@ mod test {
             assert_ne!(res.len(), 0);
 
             // they should all have non-empty ids
-            let ok = res.iter().fold(false, |ok, reg| ok || reg.id != "");
-            assert_eq!(ok, true);
+            let ok = res.iter().any(|reg| !reg.id.as_str().is_empty());
+            assert!(ok);
         }
     }
 }
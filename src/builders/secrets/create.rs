@@ -0,0 +1,104 @@
+imports!();
+
+new_builder!(
+    /// `POST /api/users/:id/secrets`
+    CreateUserSecret,
+);
+
+use crate::builders::secrets::get::UserSecretsBuilder;
+
+exec!(
+    CreateUserSecret -> () = POST,
+);
+
+from!(
+    @UserSecrets
+        -> CreateUserSecret,
+);
+
+impl_builder!(
+    @UserSecrets
+        /// Creates a new secret for the user.
+        +> create [] -> CreateUserSecret (req: crate::models::SecretRequest),
+);
+
+#[cfg(test)]
+mod test {
+    use crate::client::test::client;
+    use crate::client::Executor;
+    use crate::models::SecretRequest;
+
+    #[tokio::test]
+    async fn test_user_secret_lifecycle() {
+        let c = client();
+        let name = "CODER_RS_TEST_SECRET";
+
+        let mut req = SecretRequest {
+            name: name.into(),
+            value: "initial".into(),
+            description: "created by coder.rs tests".into(),
+        };
+
+        c.users()
+            .me()
+            .secrets()
+            .create(&req)
+            .expect("build request")
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        let secrets = c
+            .users()
+            .me()
+            .secrets()
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        let secret = secrets
+            .into_iter()
+            .find(|s| s.name == name)
+            .expect("created secret should be listed");
+
+        req.value = "updated".into();
+        c.users()
+            .me()
+            .secrets()
+            .update(&secret.id, &req)
+            .expect("build request")
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        let res = c
+            .users()
+            .me()
+            .secrets()
+            .get(&secret.id)
+            .expect("build request")
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+        assert_eq!(res.value, Some("updated".into()));
+
+        c.users()
+            .me()
+            .secrets()
+            .delete(&secret.id)
+            .expect("build request")
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+    }
+}
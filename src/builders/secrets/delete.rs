@@ -0,0 +1,23 @@
+imports!();
+
+new_builder!(
+    /// `DELETE /api/users/:id/secrets/:id`
+    DeleteUserSecret,
+);
+
+use crate::builders::secrets::get::UserSecretsBuilder;
+
+exec!(
+    DeleteUserSecret -> () = DELETE,
+);
+
+from!(
+    @UserSecrets
+        -> DeleteUserSecret,
+);
+
+impl_builder!(
+    @UserSecrets
+        /// Deletes a secret by its id.
+        => delete [] -> DeleteUserSecret = id: SecretId,
+);
@@ -0,0 +1,56 @@
+imports!();
+
+new_builder!(
+    /// `/api/users/:id/secrets/:id`
+    UserSecret,
+    /// `/api/users/:id/secrets`
+    UserSecrets,
+);
+
+use crate::builders::users::get::UserBuilder;
+
+exec!(
+    UserSecret -> crate::models::Secret,
+    UserSecrets -> Vec<crate::models::Secret>,
+);
+
+from!(
+    @User
+        -> UserSecrets,
+    @UserSecrets
+        -> UserSecret,
+);
+
+impl_builder!(
+    @User
+        /// Queries all secrets belonging to the user. Secret values are not returned.
+        -> secrets ["secrets"] -> UserSecrets,
+    @UserSecrets
+        /// Queries a secret by its id, including its value.
+        => get [] -> UserSecret = id: SecretId,
+);
+
+#[cfg(test)]
+mod test {
+    use crate::client::test::client;
+    use crate::client::Executor;
+
+    #[tokio::test]
+    async fn test_user_secrets() {
+        let c = client();
+
+        let res = c
+            .users()
+            .me()
+            .secrets()
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        // they should all have non-empty ids
+        let ok = res.iter().all(|s| !s.id.is_empty());
+        assert!(ok);
+    }
+}
@@ -0,0 +1,23 @@
+imports!();
+
+new_builder!(
+    /// `PUT /api/users/:id/secrets/:id`
+    UpdateUserSecret,
+);
+
+use crate::builders::secrets::get::UserSecretsBuilder;
+
+exec!(
+    UpdateUserSecret -> () = PUT,
+);
+
+from!(
+    @UserSecrets
+        -> UpdateUserSecret,
+);
+
+impl_builder!(
+    @UserSecrets
+        /// Updates the name, value and description of a secret by its id.
+        #> update [] -> UpdateUserSecret = id: SecretId (req: crate::models::SecretRequest),
+);
@@ -27,7 +27,7 @@ impl_builder!(
         -> services ["services"] -> OrgServices,
     @OrgServices
         /// Queries a specific service in an organization by its id.
-        => get [] -> OrgService = id,
+        => get [] -> OrgService = id: ServiceId,
 );
 
 #[cfg(test)]
@@ -45,6 +45,7 @@ mod test {
             let res = c
                 .orgs()
                 .get(ORG_ID)
+                .expect("build request")
                 .services()
                 .execute()
                 .await
@@ -56,8 +57,8 @@ mod test {
             assert_ne!(res.len(), 0);
 
             // they should all have non-empty ids
-            let ok = res.iter().fold(false, |ok, svc| ok || svc.id != "");
-            assert_eq!(ok, true);
+            let ok = res.iter().any(|svc| !svc.id.as_str().is_empty());
+            assert!(ok);
         }
 
         #[tokio::test]
@@ -67,8 +68,10 @@ mod test {
             let res = c
                 .orgs()
                 .get(ORG_ID)
+                .expect("build request")
                 .services()
                 .get(SERVICE_ID)
+                .expect("build request")
                 .execute()
                 .await
                 .expect("send request")
@@ -0,0 +1,48 @@
+imports!();
+
+new_builder!(
+    /// `/api/users/:id/sshkey`
+    UserSshKey,
+);
+
+use crate::builders::users::get::UserBuilder;
+
+exec!(
+    UserSshKey -> crate::models::SshKey,
+);
+
+from!(
+    @User
+        -> UserSshKey,
+);
+
+impl_builder!(
+    @User
+        /// Queries the user's SSH key, which is used for accessing their environments and git
+        /// repositories.
+        -> ssh_key ["sshkey"] -> UserSshKey,
+);
+
+#[cfg(test)]
+mod test {
+    use crate::client::test::{client, ids::*};
+    use crate::client::Executor;
+
+    #[tokio::test]
+    async fn test_user_ssh_key() {
+        let c = client();
+
+        let res = c
+            .users()
+            .get(USER_ID)
+            .expect("build request")
+            .ssh_key()
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+
+        assert!(res.public_key.starts_with("ssh-"));
+    }
+}
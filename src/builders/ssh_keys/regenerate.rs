@@ -0,0 +1,23 @@
+imports!();
+
+new_builder!(
+    /// `POST /api/users/:id/sshkey`
+    RegenerateSshKey,
+);
+
+use crate::builders::ssh_keys::get::UserSshKeyBuilder;
+
+exec!(
+    RegenerateSshKey -> crate::models::SshKey = POST,
+);
+
+from!(
+    @UserSshKey
+        -> RegenerateSshKey,
+);
+
+impl_builder!(
+    @UserSshKey
+        /// Replaces the user's SSH key with a newly generated one, returning the new key.
+        -> regenerate [] -> RegenerateSshKey,
+);
@@ -1,5 +1,7 @@
 imports!();
 
+use crate::batch::Batch;
+
 new_builder!(
     /// `/api/users/:id`
     User,
@@ -27,9 +29,21 @@ impl_builder!(
         /// Queries the current user.
         -> me ["me"] -> User,
         /// Queries a user by their id.
-        => get [] -> User = id,
+        => get [] -> User = id: UserId,
 );
 
+impl UsersBuilder {
+    /// Queries many users by their ids concurrently, returning a result for each id in order.
+    /// Each user is requested separately.
+    pub fn get_many<I>(self, ids: I) -> Batch<UserBuilder>
+    where
+        I: IntoIterator,
+        I::Item: Into<crate::models::UserId>,
+    {
+        Batch::from_ids(&self, ids, Self::get)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::client::test::{client, ids::*};
@@ -42,6 +56,7 @@ mod test {
         let res = c
             .users()
             .get(USER_ID)
+            .expect("build request")
             .execute()
             .await
             .expect("send request")
@@ -68,7 +83,7 @@ mod test {
         assert_ne!(res.len(), 0);
 
         // they should all have non-empty ids
-        let ok = res.iter().fold(true, |ok, usr| ok && usr.id != "");
-        assert_eq!(ok, true);
+        let ok = res.iter().all(|usr| !usr.id.as_str().is_empty());
+        assert!(ok);
     }
 }
@@ -1,43 +1,131 @@
-// TLS imports
-#[cfg(feature = "rustls")]
-type HttpsConnector = hyper_rustls::HttpsConnector<hyper::client::HttpConnector>;
-#[cfg(feature = "rust-native-tls")]
-use hyper_tls;
-#[cfg(feature = "rust-native-tls")]
-type HttpsConnector = hyper_tls::HttpsConnector<hyper::client::HttpConnector>;
-
 use std::sync::Arc;
 
+use hyper::header::HeaderValue;
+use hyper::Request;
 use hyper::StatusCode;
 use hyper::{self, Body};
-use hyper::{Client, Request};
 use url::Url;
 
 use crate::error::{ApiError, Error};
 use crate::headers::Headers;
+use crate::transport::{Bytes, HyperTransport, Transport};
 use async_trait::async_trait;
 use serde::de::DeserializeOwned;
 
-const VERSION: &'static str = env!("CARGO_PKG_VERSION");
-
 pub struct Coder {
     pub(crate) url: Url,
-    pub(crate) token: &'static str,
-    pub(crate) client: Arc<Client<HttpsConnector>>,
+    pub(crate) token: HeaderValue,
+    pub(crate) client: Arc<dyn Transport>,
 }
 
-const API_PREFIX: &'static str = "/api";
+const API_PREFIX: &str = "/api";
 
 impl Coder {
+    /// Creates a client for the manager at `uri`. On Unix, URLs such as
+    /// `unix:///var/run/coder.sock` connect to a manager listening on a Unix domain socket, the
+    /// same as [`Coder::unix`].
     pub fn new<T: ToString>(uri: String, token: T) -> Result<Self, Error> {
+        let url = uri.parse::<Url>()?;
+        if url.scheme() == "unix" {
+            #[cfg(unix)]
+            return Self::unix(unix_socket_path(&uri, &url)?, token);
+            #[cfg(not(unix))]
+            return Err(Error::InvalidBaseUrl {
+                url: uri,
+                reason: "unix sockets aren't supported on this platform",
+            });
+        }
+        Self::from_url(uri, url, token, HyperTransport::new())
+    }
+
+    /// Creates a client for a manager listening on the Unix domain socket at `path`. Requests are
+    /// sent to `http://localhost` over the socket.
+    #[cfg(unix)]
+    pub fn unix<P, T>(path: P, token: T) -> Result<Self, Error>
+    where
+        P: Into<std::path::PathBuf>,
+        T: ToString,
+    {
+        let url = Url::parse("http://localhost").expect("localhost url");
+        let transport = crate::transport::UnixTransport::new(path);
+        Self::from_url(url.to_string(), url, token, transport)
+    }
+
+    /// Creates a client that sends its requests through a custom transport. Returns an error if the
+    /// URL isn't an http or https URL, or if the token isn't a valid header value.
+    pub fn with_transport<T, R>(uri: String, token: T, transport: R) -> Result<Self, Error>
+    where
+        T: ToString,
+        R: Transport + 'static,
+    {
+        let url = uri.parse::<Url>()?;
+        Self::from_url(uri, url, token, transport)
+    }
+
+    fn from_url<T, R>(uri: String, url: Url, token: T, transport: R) -> Result<Self, Error>
+    where
+        T: ToString,
+        R: Transport + 'static,
+    {
+        let mut token = HeaderValue::from_str(&token.to_string()).map_err(http::Error::from)?;
+        token.set_sensitive(true);
+
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(Error::InvalidBaseUrl {
+                url: uri,
+                reason: "scheme must be http or https",
+            });
+        }
+        if url.cannot_be_a_base() {
+            return Err(Error::InvalidBaseUrl {
+                url: uri,
+                reason: "must be an absolute URL with a host",
+            });
+        }
+
         Ok(Self {
-            url: uri.parse::<Url>()?.join(API_PREFIX)?,
-            token: Box::leak(token.to_string().into_boxed_str()),
-            client: Arc::new(Client::builder().build(HttpsConnector::new())),
+            url: url.join(API_PREFIX)?,
+            token,
+            client: Arc::new(transport),
         })
     }
 }
 
+/// Returns the socket path of a `unix://` URL.
+#[cfg(unix)]
+fn unix_socket_path(uri: &str, url: &Url) -> Result<std::path::PathBuf, Error> {
+    if url.host_str().is_some_and(|h| !h.is_empty()) || url.path().len() <= 1 {
+        return Err(Error::InvalidBaseUrl {
+            url: uri.to_string(),
+            reason: "must have an absolute socket path, such as unix:///var/run/coder.sock",
+        });
+    }
+    let path = percent_encoding::percent_decode_str(url.path()).decode_utf8_lossy();
+    Ok(path.into_owned().into())
+}
+
+/// The route template of a request, such as `/orgs/:id/members`, with route variables replaced by
+/// their names. Request builders attach it as a request extension so middleware can group requests
+/// by route.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Route(pub(crate) String);
+
+impl Route {
+    pub fn as_str(&self) -> &str {
+        if self.0.is_empty() {
+            "/"
+        } else {
+            &self.0
+        }
+    }
+}
+
+impl std::fmt::Display for Route {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug)]
 pub struct ApiResponse<T: DeserializeOwned> {
     pub headers: Headers,
@@ -45,45 +133,383 @@ pub struct ApiResponse<T: DeserializeOwned> {
     pub response: Result<T, ApiError>,
 }
 
+/// A response whose body hasn't been deserialized, returned by [`Executor::execute_raw`].
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub headers: Headers,
+    pub status_code: StatusCode,
+    pub body: Bytes,
+}
+
+impl RawResponse {
+    /// Deserializes the body into `T`, or into an [`ApiError`] if the request failed, the same way
+    /// as [`Executor::execute`].
+    pub fn json<T: DeserializeOwned>(&self) -> Result<ApiResponse<T>, Error> {
+        self.clone().into_api_response()
+    }
+
+    fn into_api_response<T: DeserializeOwned>(self) -> Result<ApiResponse<T>, Error> {
+        // Routes that return no content are deserialized as `null`.
+        let body = if self.body.is_empty() {
+            Bytes::from_static(b"null")
+        } else {
+            self.body
+        };
+        let response = if self.status_code.is_success() {
+            Ok(deserialize::<T>(&body)?)
+        } else {
+            Err(deserialize::<ApiError>(&body)?)
+        };
+
+        Ok(ApiResponse {
+            headers: self.headers,
+            status_code: self.status_code,
+            response,
+        })
+    }
+}
+
+impl From<http::Response<Bytes>> for RawResponse {
+    fn from(res: http::Response<Bytes>) -> Self {
+        let (parts, body) = res.into_parts();
+        Self {
+            headers: parts.headers.into(),
+            status_code: parts.status,
+            body,
+        }
+    }
+}
+
+/// Sends a request built by a request builder. Builders aren't consumed, so the same request can
+/// be executed any number of times.
 #[async_trait]
-pub trait Executor {
+pub trait Executor: Send + Sync {
     type T: DeserializeOwned;
 
-    async fn execute(self) -> Result<ApiResponse<Self::T>, Error>;
+    /// Returns the request to send. Used to implement the other methods. Implementations outside
+    /// this crate that only override [`execute`](Self::execute) keep the default, which returns
+    /// an error from the other methods.
+    #[doc(hidden)]
+    fn to_request(&self) -> Result<Request<Bytes>, Error> {
+        Err(unsupported())
+    }
+
+    /// Returns the transport to send the request with. Used to implement the other methods.
+    #[doc(hidden)]
+    fn transport(&self) -> &dyn Transport {
+        &Unsupported
+    }
+
+    /// Sends the request and deserializes the response into `T`.
+    async fn execute(&self) -> Result<ApiResponse<Self::T>, Error> {
+        self.execute_as().await
+    }
+
+    /// Sends the request and returns the response without deserializing its body.
+    async fn execute_raw(&self) -> Result<RawResponse, Error> {
+        execute_raw(self.transport(), self.to_request()?).await
+    }
+
+    /// Sends the request and deserializes the response into `U` instead of `T`, for example to
+    /// read fields the models don't have yet.
+    async fn execute_as<U: DeserializeOwned>(&self) -> Result<ApiResponse<U>, Error> {
+        execute(self.transport(), self.to_request()?).await
+    }
+
+    /// Sends the request and deserializes the response into a `serde_json::Value`.
+    async fn execute_value(&self) -> Result<ApiResponse<serde_json::Value>, Error> {
+        self.execute_as().await
+    }
+}
+
+/// The transport used by executors that don't provide one.
+struct Unsupported;
+
+fn unsupported() -> Error {
+    Error::Transport("this executor doesn't support sending requests directly".into())
+}
+
+#[async_trait]
+impl Transport for Unsupported {
+    async fn send(&self, _req: Request<Bytes>) -> Result<http::Response<Bytes>, Error> {
+        Err(unsupported())
+    }
 }
 
 impl Coder {
-    /// Returns a populated request for creating custom queries.
+    /// Returns a populated request for creating custom queries. See [`Coder::request`] for sending
+    /// custom queries through the client.
     #[inline]
     pub fn new_request(&self) -> Result<Request<Body>, Error> {
-        Ok(Request::builder()
-            .method(hyper::Method::GET)
-            .uri(self.url.to_string())
-            .header("User-Agent", format!("coder.rs {}", VERSION))
-            .header("Session-Token", self.token)
-            .body(Body::empty())?)
+        let mut req = self.new_base_request();
+        *req.uri_mut() = self.url.as_str().parse().map_err(http::Error::from)?;
+        Ok(req.map(Body::from))
+    }
+
+    /// Returns a populated request with a buffered body, as sent by request builders. The URI is set
+    /// by the builder.
+    #[inline]
+    pub(crate) fn new_base_request(&self) -> Request<Bytes> {
+        let mut req = Request::new(Bytes::new());
+        let headers = req.headers_mut();
+        headers.insert(
+            hyper::header::USER_AGENT,
+            HeaderValue::from_static(concat!("coder.rs ", env!("CARGO_PKG_VERSION"))),
+        );
+        headers.insert("Session-Token", self.token.clone());
+        req
+    }
+}
+
+/// Sends a built request through the transport and deserializes the response. This is the common
+/// implementation of `Executor::execute` for all request builders.
+pub(crate) async fn execute<T: DeserializeOwned>(
+    client: &dyn Transport,
+    req: Request<Bytes>,
+) -> Result<ApiResponse<T>, Error> {
+    run(client, req, RawResponse::into_api_response).await
+}
+
+/// Sends a built request through the transport without deserializing the response.
+pub(crate) async fn execute_raw(
+    client: &dyn Transport,
+    req: Request<Bytes>,
+) -> Result<RawResponse, Error> {
+    run(client, req, Ok).await
+}
+
+#[cfg(not(feature = "tracing"))]
+async fn run<R: Outcome>(
+    client: &dyn Transport,
+    req: Request<Bytes>,
+    parse: impl FnOnce(RawResponse) -> Result<R, Error>,
+) -> Result<R, Error> {
+    send(client, req, parse).await
+}
+
+/// Each request runs in a `coder.request` span. Only the method and route template are recorded,
+/// never the full URL, headers or bodies, so session tokens and secret values aren't logged.
+#[cfg(feature = "tracing")]
+async fn run<R: Outcome>(
+    client: &dyn Transport,
+    req: Request<Bytes>,
+    parse: impl FnOnce(RawResponse) -> Result<R, Error>,
+) -> Result<R, Error> {
+    use tracing::field::Empty;
+    use tracing::Instrument;
+
+    let route = req.extensions().get::<Route>().cloned().unwrap_or_default();
+    let span = tracing::info_span!(
+        "coder.request",
+        method = %req.method(),
+        route = %route,
+        status = Empty,
+        latency_ms = Empty,
+    );
+
+    let start = std::time::Instant::now();
+    let record_status = |res: RawResponse| {
+        span.record("status", res.status_code.as_u16());
+        parse(res)
+    };
+    let res = send(client, req, record_status)
+        .instrument(span.clone())
+        .await;
+    span.record("latency_ms", start.elapsed().as_millis() as u64);
+    if let Err(err) = &res {
+        tracing::debug!(parent: &span, error = %err, "request failed");
+    }
+    res
+}
+
+async fn send<R: Outcome>(
+    client: &dyn Transport,
+    req: Request<Bytes>,
+    parse: impl FnOnce(RawResponse) -> Result<R, Error>,
+) -> Result<R, Error> {
+    #[cfg(feature = "metrics")]
+    let metrics = crate::telemetry::RequestMetrics::start(&req);
+
+    let res = client.send(req).await.map(RawResponse::from);
+    #[cfg(feature = "metrics")]
+    let status = res.as_ref().ok().map(|res| res.status_code);
+
+    let res = res.and_then(parse);
+
+    #[cfg(feature = "metrics")]
+    metrics.finish(status, &res);
+    res
+}
+
+/// The parsed responses returned by `send`.
+#[cfg(feature = "metrics")]
+trait Outcome: crate::telemetry::ErrorCode {}
+#[cfg(feature = "metrics")]
+impl<R: crate::telemetry::ErrorCode> Outcome for R {}
+
+#[cfg(not(feature = "metrics"))]
+trait Outcome {}
+#[cfg(not(feature = "metrics"))]
+impl<R> Outcome for R {}
+
+fn deserialize<T: DeserializeOwned>(body: &[u8]) -> Result<T, serde_json::Error> {
+    let res = serde_json::from_slice(body);
+    #[cfg(feature = "tracing")]
+    if let Err(err) = &res {
+        // The body may contain secrets, so only its size is logged.
+        tracing::warn!(
+            error = %err,
+            target_type = std::any::type_name::<T>(),
+            body_len = body.len(),
+            "failed to deserialize response"
+        );
     }
+    res
 }
 
 #[cfg(test)]
 pub(crate) mod test {
     pub(crate) mod ids {
-        pub const ENV_ID: &'static str = "5ed15061-d7d3db1d91600a4fed28f6ed";
-        pub const IMAGE_ID: &'static str = "5ea8a569-596e6afd9301c23f8dabd87c";
-        pub const IMAGE_TAG_ID: &'static str = "latest";
-        pub const MEMBER_ID: &'static str = "5e876cf4-10abe9b2e54eb609c5ec1870";
-        pub const ORG_ID: &'static str = "default";
-        pub const REG_ID: &'static str = "5ea8a565-bdec42be59ffe9cf6b131e7c";
-        pub const SERVICE_ID: &'static str = "5f15b3a2-57f7a823e4d379409978edbf";
-        pub const USER_ID: &'static str = "5e876cf4-10abe9b2e54eb609c5ec1870";
+        pub const ENV_ID: &str = "5ed15061-d7d3db1d91600a4fed28f6ed";
+        pub const IMAGE_ID: &str = "5ea8a569-596e6afd9301c23f8dabd87c";
+        pub const IMAGE_TAG_ID: &str = "latest";
+        pub const MEMBER_ID: &str = "5e876cf4-10abe9b2e54eb609c5ec1870";
+        pub const ORG_ID: &str = "default";
+        pub const REG_ID: &str = "5ea8a565-bdec42be59ffe9cf6b131e7c";
+        pub const SERVICE_ID: &str = "5f15b3a2-57f7a823e4d379409978edbf";
+        pub const USER_ID: &str = "5e876cf4-10abe9b2e54eb609c5ec1870";
     }
 
     use super::*;
-    use std::env;
 
     pub(crate) fn client() -> Coder {
-        let url = env::var("MANAGER_URL").expect("no MANAGER_URL env provided");
-        let api_key = env::var("API_KEY").expect("no API_KEY env provided");
-        Coder::new(url, api_key).unwrap()
+        Coder::from_env().expect("no CODER_URL or CODER_TOKEN env provided")
+    }
+
+    mod execute {
+        use super::*;
+        use crate::transport::test::MockTransport;
+
+        fn mock(body: &'static str) -> Coder {
+            let t = MockTransport::new(200, body);
+            Coder::with_transport("https://coder.example.com".into(), "key", t).unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_builder_reuse() {
+            fn assert_builder<B: Executor + Clone + Send + Sync>(_: &B) {}
+
+            let t = MockTransport::new(200, r#"["default"]"#);
+            let c = Coder::with_transport("https://coder.example.com".into(), "key", t.clone())
+                .unwrap();
+
+            let q = c.orgs().namespaces();
+            assert_builder(&q);
+            let q2 = q.clone();
+            for q in &[&q, &q, &q2] {
+                let res = q.execute().await.unwrap();
+                assert_eq!(res.response.unwrap(), vec!["default".to_string()]);
+            }
+            assert_eq!(t.requests.lock().unwrap().len(), 3);
+        }
+
+        #[test]
+        fn test_invalid_token() {
+            let t = MockTransport::new(200, "null");
+            let res = Coder::with_transport("https://coder.example.com".into(), "bad\ntoken", t);
+            assert!(matches!(res, Err(Error::HyperHttp(_))));
+        }
+
+        #[tokio::test]
+        async fn test_execute_raw() {
+            let c = mock(r#"[{"name":"default","new_field":1}]"#);
+
+            let res = c.orgs().namespaces().execute_raw().await.unwrap();
+            assert_eq!(res.status_code, 200);
+            assert_eq!(res.headers["Content-Type"], "application/json");
+            assert_eq!(res.body.as_ref(), br#"[{"name":"default","new_field":1}]"#);
+
+            // The namespaces are strings, so the body can't be deserialized as `T`, but the raw
+            // response is still available.
+            assert!(matches!(res.json::<Vec<String>>(), Err(Error::Serde(_))));
+            let res = res.json::<serde_json::Value>().unwrap();
+            assert_eq!(res.response.unwrap()[0]["new_field"], 1);
+        }
+
+        #[tokio::test]
+        async fn test_custom_executor() {
+            struct Fixed;
+
+            #[async_trait]
+            impl Executor for Fixed {
+                type T = Vec<String>;
+
+                async fn execute(&self) -> Result<ApiResponse<Self::T>, Error> {
+                    Ok(ApiResponse {
+                        headers: http::HeaderMap::new().into(),
+                        status_code: StatusCode::OK,
+                        response: Ok(vec!["default".into()]),
+                    })
+                }
+            }
+
+            let res = Fixed.execute().await.unwrap();
+            assert_eq!(res.response.unwrap(), vec!["default".to_string()]);
+            assert!(matches!(
+                Fixed.execute_raw().await,
+                Err(Error::Transport(_))
+            ));
+        }
+
+        #[tokio::test]
+        async fn test_execute_as() {
+            #[derive(serde::Deserialize)]
+            struct Namespace {
+                name: String,
+            }
+
+            let c = mock(r#"[{"name":"default"}]"#);
+            let res = c
+                .orgs()
+                .namespaces()
+                .execute_as::<Vec<Namespace>>()
+                .await
+                .unwrap();
+            assert_eq!(res.response.unwrap()[0].name, "default");
+
+            let res = c.orgs().namespaces().execute_value().await.unwrap();
+            assert_eq!(res.response.unwrap()[0]["name"], "default");
+        }
+
+        #[cfg(feature = "tracing")]
+        #[test]
+        fn test_request_span() {
+            use crate::middleware::test::trace::{record, SpanRecorder};
+
+            let t = MockTransport::new(404, r#"{"error":{"msg":"member not found"}}"#);
+            let c = Coder::with_transport("https://coder.example.com".into(), "secret", t).unwrap();
+            let q = c
+                .orgs()
+                .get("default")
+                .expect("build request")
+                .members()
+                .get("5e876cf4-10abe9b2e54eb609c5ec1870")
+                .expect("build request");
+
+            let recorder = SpanRecorder::default();
+            let res = record(&recorder, q.execute()).expect("send request");
+            assert!(res.response.is_err());
+
+            let spans = recorder.spans("coder.request");
+            assert_eq!(spans.len(), 1);
+            let fields = &spans[0].fields;
+            assert_eq!(fields["method"], "GET");
+            assert_eq!(fields["route"], "/orgs/:id/members/:user_id");
+            assert_eq!(fields["status"], "404");
+            assert!(fields.contains_key("latency_ms"));
+            assert!(!fields
+                .values()
+                .any(|v| v.contains("secret") || v.contains("5e876cf4")));
+        }
     }
 }
@@ -0,0 +1,148 @@
+//! Creating clients from the environment or from the Coder CLI's stored login.
+//!
+//! ```rust,no_run
+//! # fn run() -> Result<(), coder::Error> {
+//! use coder::Coder;
+//!
+//! // Uses `CODER_URL` and `CODER_TOKEN`, falling back to the login from `coder login`.
+//! let c = Coder::from_env().or_else(|_| Coder::from_cli_config())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::client::Coder;
+use crate::error::{ConfigError, Error};
+
+/// The environment variable read by [`Coder::from_env`] for the manager URL.
+pub const URL_ENV: &str = "CODER_URL";
+/// The environment variable read by [`Coder::from_env`] for the session token.
+pub const TOKEN_ENV: &str = "CODER_TOKEN";
+
+impl Coder {
+    /// Creates a client for the manager at `CODER_URL`, authenticated with `CODER_TOKEN`. These
+    /// are the same variables the Coder CLI reads.
+    pub fn from_env() -> Result<Self, Error> {
+        Self::from_vars(|key| std::env::var_os(key))
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<std::ffi::OsString>) -> Result<Self, Error> {
+        let get = |key: &'static str| match var(key) {
+            Some(v) if v.is_empty() => Err(ConfigError::MissingEnv(key.into())),
+            Some(v) => v
+                .into_string()
+                .map_err(|_| ConfigError::InvalidEnv(key.into())),
+            None => Err(ConfigError::MissingEnv(key.into())),
+        };
+        let url = get(URL_ENV)?;
+        let token = get(TOKEN_ENV)?;
+        Self::new(url, token)
+    }
+
+    /// Creates a client from the URL and session token stored by `coder login`. The CLI stores
+    /// them in the `url` and `session` files of the `coder` directory in the user config
+    /// directory, such as `~/.config/coder` on Linux.
+    pub fn from_cli_config() -> Result<Self, Error> {
+        let dir = cli_config_dir().ok_or(ConfigError::NoConfigDir)?;
+        Self::from_cli_dir(&dir)
+    }
+
+    fn from_cli_dir(dir: &Path) -> Result<Self, Error> {
+        let url = read_config_file(&dir.join("url"))?;
+        let token = read_config_file(&dir.join("session"))?;
+        Self::new(url, token)
+    }
+}
+
+/// Returns the directory the Coder CLI stores its login in.
+pub(crate) fn cli_config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("coder"))
+}
+
+/// Reads a single value from a file, ignoring surrounding whitespace.
+pub(crate) fn read_config_file(path: &Path) -> Result<String, ConfigError> {
+    let value = std::fs::read_to_string(path).map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => ConfigError::MissingFile(path.into()),
+        _ => ConfigError::ReadFile {
+            path: path.into(),
+            source: err,
+        },
+    })?;
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(ConfigError::EmptyFile(path.into()));
+    }
+    Ok(value.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::ffi::OsString;
+
+    use super::*;
+
+    fn from_vars(vars: &[(&str, &str)]) -> Result<Coder, Error> {
+        let vars: HashMap<_, _> = vars.iter().cloned().collect();
+        Coder::from_vars(|k| vars.get(k).map(OsString::from))
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("coder-rs-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_from_env() {
+        let c = from_vars(&[
+            ("CODER_URL", "https://coder.example.com"),
+            ("CODER_TOKEN", "key"),
+        ])
+        .unwrap();
+        assert_eq!(c.url.as_str(), "https://coder.example.com/api");
+        assert_eq!(c.token, "key");
+
+        let err = from_vars(&[("CODER_TOKEN", "key")]).err().unwrap();
+        assert!(matches!(
+            err,
+            Error::Config(ConfigError::MissingEnv(name)) if name == "CODER_URL"
+        ));
+        let err = from_vars(&[
+            ("CODER_URL", "https://coder.example.com"),
+            ("CODER_TOKEN", ""),
+        ])
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "config error: environment variable CODER_TOKEN is not set or is empty"
+        );
+    }
+
+    #[test]
+    fn test_from_cli_dir() {
+        let dir = temp_dir("cli");
+        std::fs::write(dir.join("url"), "https://coder.example.com\n").unwrap();
+
+        let err = Coder::from_cli_dir(&dir).err().unwrap();
+        match err {
+            Error::Config(ConfigError::MissingFile(path)) => assert_eq!(path, dir.join("session")),
+            err => panic!("unexpected error {}", err),
+        }
+
+        std::fs::write(dir.join("session"), "  \n").unwrap();
+        let err = Coder::from_cli_dir(&dir).err().unwrap();
+        assert!(matches!(err, Error::Config(ConfigError::EmptyFile(_))));
+
+        std::fs::write(dir.join("session"), "key\n").unwrap();
+        let c = Coder::from_cli_dir(&dir).unwrap();
+        assert_eq!(c.url.as_str(), "https://coder.example.com/api");
+        assert_eq!(c.token, "key");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use serde::Deserialize;
 use thiserror::Error as ErrorImpl;
 
@@ -9,8 +11,74 @@ pub enum Error {
     HyperHttp(#[from] hyper::http::Error),
     #[error("serde error: {0}")]
     Serde(#[from] serde_json::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
     #[error("url parse error: {0}")]
     UrlParse(#[from] url::ParseError),
+    #[error("transport error: {0}")]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+    #[error("blocking runtime shut down before the request finished")]
+    RuntimeShutdown,
+    #[error("invalid base url {url:?}: {reason}")]
+    InvalidBaseUrl { url: String, reason: &'static str },
+    #[error("invalid proxy url {url:?}: {reason}")]
+    InvalidProxyUrl { url: String, reason: &'static str },
+    #[error("builder error: {0}")]
+    Builder(#[from] BuilderError),
+    #[error("invalid root certificate: {0}")]
+    InvalidCertificate(String),
+    #[error("config error: {0}")]
+    Config(#[from] ConfigError),
+}
+
+/// An error loading client configuration, such as in [`Coder::from_env`](crate::Coder::from_env).
+/// Each variant names the environment variable or file that couldn't be used.
+#[derive(ErrorImpl, Debug)]
+pub enum ConfigError {
+    #[error("environment variable {0} is not set or is empty")]
+    MissingEnv(String),
+    #[error("environment variable {0} is not valid unicode")]
+    InvalidEnv(String),
+    #[error("no user config directory found")]
+    NoConfigDir,
+    #[error("{} doesn't exist", .0.display())]
+    MissingFile(PathBuf),
+    #[error("{} is empty", .0.display())]
+    EmptyFile(PathBuf),
+    #[error("failed to read {}: {source}", path.display())]
+    ReadFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("failed to parse {}: {source}", path.display())]
+    ParseProfiles {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("no profile named {name:?} in {}", path.display())]
+    UnknownProfile { name: String, path: PathBuf },
+    #[error("token command {command:?} failed: {reason}")]
+    TokenCommand { command: String, reason: String },
+    #[error("invalid CA file {}: {reason}", path.display())]
+    InvalidCaFile { path: PathBuf, reason: String },
+}
+
+/// An error building a request, returned by request builder methods before anything is sent.
+#[derive(ErrorImpl, Debug, Clone, PartialEq, Eq)]
+pub enum BuilderError {
+    /// A route variable, such as a resource id, isn't valid for its route.
+    #[error("invalid {kind} {value:?}: {reason}")]
+    InvalidId {
+        kind: &'static str,
+        value: String,
+        reason: &'static str,
+    },
+    /// A request body couldn't be serialized.
+    #[error("failed to serialize request body: {0}")]
+    Body(String),
+    /// The request URL couldn't be converted into a request URI.
+    #[error("invalid request uri {0:?}")]
+    InvalidUri(String),
 }
 
 #[derive(Deserialize, Debug)]
@@ -36,12 +104,14 @@ mod test {
         use super::*;
 
         #[tokio::test]
+        #[allow(unused_must_use)]
         async fn test_api_error() {
             let c = client();
 
             let res = c
                 .users()
-                .get("some random id")
+                .get("5e876cf4-000000000000000000000000")
+                .expect("build request")
                 .execute()
                 .await
                 .expect("send request")
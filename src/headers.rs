@@ -1,6 +1,6 @@
 use hyper::HeaderMap;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Headers(HeaderMap);
 
 impl From<HeaderMap> for Headers {
@@ -13,7 +13,7 @@
 //!
 //! ## Installation
 //!
-//! Coder.rs has been tested to work on Rust 1.40+
+//! Coder.rs requires Rust 1.74 or newer.
 //!
 //! Add this to your `Cargo.toml`'s `[dependencies]` section:
 //!
@@ -27,16 +27,14 @@
 //! struct for creating requests.
 //!
 //! ```rust
-//! use std::env;
 //! use std::error::Error;
 //!
 //! use coder::{Coder, Executor};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn Error>> {
-//!     let url = env::var("MANAGER_URL")?;
-//!     let api_key = env::var("API_KEY")?;
-//!     let c = Coder::new(url, api_key)?;
+//!     // Reads the CODER_URL and CODER_TOKEN environment variables.
+//!     let c = Coder::from_env()?;
 //!
 //!     let res = c.users().me().execute().await?;
 //!     dbg!(res);
@@ -79,10 +77,26 @@
 //! // }
 //! ```
 //!
+//! ## Proxies
+//!
+//! Requests are sent through the proxies in the `HTTPS_PROXY`, `HTTP_PROXY` and `ALL_PROXY`
+//! environment variables, except for the hosts in `NO_PROXY`. HTTP and SOCKS5 proxies are supported,
+//! including proxy authentication. Proxies can also be configured explicitly with
+//! [`coder::transport::Proxy`](https://docs.rs/coder/latest/coder/transport/struct.Proxy.html).
+//!
 //! ## Features
 //!
 //! * `rustls` - Uses the [`rustls`](https://docs.rs/rustls/) pure Rust TLS implementation. (default)
 //! * `rust-native-tls` - Uses [`native-tls`](https://docs.rs/native-tls/) for TLS which links against the OS default.
+//! * `strict` - Requires every model field to be present and non-null when deserializing, instead of
+//!   tolerating fields that are missing or `null` on older managers. Useful for contract tests.
+//! * `blocking` - Provides [`coder::blocking::Executor`](https://docs.rs/coder/latest/coder/blocking/trait.Executor.html)
+//!   for executing requests synchronously, without an async runtime.
+//! * `tracing` - Opens a [`tracing`](https://docs.rs/tracing/) span for every executed request, with
+//!   the method, route template, status and latency, and provides `middleware::TraceLayer` for tracing
+//!   individual attempts.
+//! * `metrics` - Records request counts, error counts, latencies and in-flight requests per route
+//!   template through the [`metrics`](https://docs.rs/metrics/) facade.
 //!
 //! [crates-badge]: https://img.shields.io/crates/v/coder.svg
 //! [crates-url]: https://crates.io/crates/coder
@@ -105,11 +119,24 @@ mod macros;
 mod builder;
 mod error;
 
+pub mod batch;
 pub mod client;
+pub mod config;
 pub mod headers;
+pub mod middleware;
 pub mod models;
+pub mod profile;
+pub mod request;
+pub mod transport;
+
+#[cfg(feature = "metrics")]
+mod telemetry;
 
 pub mod builders;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
 pub use client::{Coder, Executor};
-pub use error::Error;
+pub use error::{BuilderError, ConfigError, Error};
+pub use http::Method;
@@ -42,30 +42,18 @@
 /// Necessary imports for when all macros are used within a file.
 macro_rules! imports {
     () => {
-        #[cfg(feature = "rustls")]
-        type HttpsConnector = hyper_rustls::HttpsConnector<hyper::client::HttpConnector>;
-        #[cfg(feature = "rust-native-tls")]
-        use hyper_tls;
-        #[cfg(feature = "rust-native-tls")]
-        type HttpsConnector = hyper_tls::HttpsConnector<hyper::client::HttpConnector>;
-        use async_trait::async_trait;
-        use hyper::client::Client;
         use paste::paste;
         use std::sync::Arc;
-        use std::sync::RwLock;
 
         use $crate::builder::Builder;
-        use $crate::client::ApiResponse;
         use $crate::client::Executor;
         use $crate::error::Error;
-
-        // This is only used in the `impl_client!` macro.
-        #[allow(unused_imports)]
-        use std::collections::HashMap;
+        use $crate::transport::Transport;
     };
 }
 
-/// Specifies new request builders. Documentation is passed through.
+/// Specifies new request builders. Builders are cheap to clone and can be executed any number of
+/// times. Documentation is passed through.
 ///
 /// # Example
 ///
@@ -78,8 +66,10 @@ macro_rules! imports {
 ///
 /// // Expands to ...
 ///
+/// #[derive(Clone)]
 /// pub struct UserBuilder { ... }
 /// /// Documentation is passed through!
+/// #[derive(Clone)]
 /// pub struct UsersBuilder { ... }
 /// ```
 macro_rules! new_builder {
@@ -89,16 +79,19 @@ macro_rules! new_builder {
     ),* $(,)?) => (
         $(paste! {
             $(#[$doc])*
+            #[derive(Clone)]
             pub struct [<$i Builder>] {
-                pub(crate) builder: Result<RwLock<Builder>, Error>,
-                pub(crate) client: Arc<Client<HttpsConnector>>,
+                pub(crate) builder: Builder,
+                pub(crate) client: Arc<dyn Transport>,
             }
         })*
     );
 }
 
 /// Marks a request builder as executable by implementing the `Executor` trait and specifying a
-/// return type. Documentation is passed through.
+/// return type. An HTTP method may optionally be given after the return type, otherwise the
+/// request is sent as a `GET`. The methods that send the request are provided by `Executor`.
+/// Documentation is passed through.
 ///
 /// # Example
 ///
@@ -111,6 +104,7 @@ macro_rules! new_builder {
 ///     Users    -> Vec<models::User>, // Returns an array of structs.
 ///     /// Documentation is passed through!
 ///     NoReturn -> (),                // Returns no body.
+///     Delete   -> () = DELETE,       // Sent as a `DELETE` request.
 /// );
 ///
 /// // Expands to ...
@@ -118,52 +112,43 @@ macro_rules! new_builder {
 /// impl Executor for UserBuilder {
 ///     type T = models::User;
 ///
-///     async fn execute(self) -> Result<ApiResponse<models::User>, Box<dyn Error>> { ... }
+///     fn to_request(&self) -> Result<Request<Bytes>, Error> { ... }
+///     fn transport(&self) -> &dyn Transport { ... }
 /// }
 /// impl Executor for UsersBuilder {
-///     type T = models::User;
+///     type T = Vec<models::User>;
 ///
-///     async fn execute(self) -> Result<ApiResponse<Vec<models::User>>, Box<dyn Error>> { ... }
+///     fn to_request(&self) -> Result<Request<Bytes>, Error> { ... }
+///     fn transport(&self) -> &dyn Transport { ... }
 /// }
 /// /// Documentation is passed through!
 /// impl Executor for NoReturnBuilder {
 ///     type T = ();
 ///
-///     async fn execute(self) -> Result<ApiResponse<()>, Box<dyn Error>> { ... }
+///     fn to_request(&self) -> Result<Request<Bytes>, Error> { ... }
+///     fn transport(&self) -> &dyn Transport { ... }
 /// }
 /// ```
 macro_rules! exec {
     ($(
         $(#[$doc:meta])*
-        $i: ident -> $t: ty
+        $i: ident -> $t: ty $(= $m: ident)?
     ),* $(,)?
     ) => (
         paste! {$(
             $(#[$doc])*
-            #[async_trait]
             impl Executor for [<$i Builder>] {
                 type T = $t;
 
-                async fn execute(self) -> Result<ApiResponse<Self::T>, Error> {
-                    let client = self.client;
-                    let builder = self.builder?.into_inner().unwrap();
-                    let req = builder.build();
-                    // dbg!(&req);
-                    let res = client.request(req).await?;
-                    let (parts, body) = res.into_parts();
-
-                    let body = hyper::body::to_bytes(body).await?;
-                    let body = if parts.status.is_success() {
-                        Ok(serde_json::from_slice::<Self::T>(&body)?)
-                    } else {
-                        Err(serde_json::from_slice::<$crate::error::ApiError>(&body)?)
-                    };
-
-                    Ok(ApiResponse {
-                        status_code: parts.status,
-                        headers: parts.headers.into(),
-                        response: body,
-                    })
+                fn to_request(&self) -> Result<hyper::Request<$crate::transport::Bytes>, Error> {
+                    #[allow(unused_mut)]
+                    let mut req = self.builder.build()?;
+                    $(*req.method_mut() = hyper::Method::$m;)?
+                    Ok(req)
+                }
+
+                fn transport(&self) -> &dyn Transport {
+                    &*self.client
                 }
             }
         )*}
@@ -229,18 +214,24 @@ macro_rules! from {
 ///     //    ||
 ///     //    \/
 ///     @GetQuery
-///         // There are two different types of impls we can generate:
+///         // There are two different types of impls for traversing routes:
 ///         //   1. `->` which generates an impl requiring no route variable.
 ///         //   2. `=>` which generates an impl requiring a route variable.
-///         //       The route variable will be appended to the provided route path.
+///         //       The route variable is validated and then appended to the provided route path.
 ///         //
 ///         // method name      new builder
 ///         //  ||   route path     ||
 ///         //  ||       ||         ||  route variable name
-///         //  \/       \/         \/          ||
-///         -> users ["users"] -> Users,     // ||
-///         /// Docs are passed through too!    \/
-///         => user  ["users"] -> User       = id,
+///         //  \/       \/         \/          ||    id type (optional)
+///         -> users ["users"] -> Users,     // ||        ||
+///         /// Docs are passed through too!    \/        \/
+///         => user  ["users"] -> User       = id: UserId,
+///
+///         // Methods can also attach a JSON request body:
+///         //   3. `+>` which generates an impl taking a request body.
+///         //   4. `#>` which generates an impl requiring a route variable and a request body.
+///         +> create [] -> CreateUser              (req: models::CreateUserRequest),
+///         #> update [] -> UpdateUser = id: UserId (req: models::UpdateUserRequest),
 /// );
 ///
 /// // Expands to ...
@@ -248,7 +239,9 @@ macro_rules! from {
 /// impl GetQueryBuilder {
 ///     pub fn users(mut self) -> UsersBuilder { ... }
 ///     /// Docs are passed through too!
-///     pub fn user<T: ToString>(mut self, id: T) -> UserBuilder { ... }
+///     pub fn user<T: Into<models::UserId>>(mut self, id: T) -> Result<UserBuilder, BuilderError> { ... }
+///     pub fn create(mut self, req: &models::CreateUserRequest) -> Result<CreateUserBuilder, BuilderError> { ... }
+///     pub fn update<T: Into<models::UserId>>(mut self, id: T, req: &models::UpdateUserRequest) -> Result<UpdateUserBuilder, BuilderError> { ... }
 /// }
 /// ```
 macro_rules! impl_builder {
@@ -266,11 +259,27 @@ macro_rules! impl_builder {
 
             // Case 2
             // This case is for methods that need a route variable such as getting a user by id.
-            // The syntax looks like: `=> <method name> [<route path>] -> <builder name> = <path variable name>`
-            // Builder name should be a struct generated by the new_builder! macro.
-            $(=> $fn2:ident [$($p2:literal)?] -> $t2:ident = $e2:ident)?
+            // The syntax looks like: `=> <method name> [<route path>] -> <builder name> = <path variable name>: <id type>`
+            // Builder name should be a struct generated by the new_builder! macro. The id type is
+            // optional and should be an id newtype from `crate::models`, otherwise any string is
+            // accepted.
+            $(=> $fn2:ident [$($p2:literal)?] -> $t2:ident = $e2:ident $(: $et2:ident)?)?
+
+            // Case 3
+            // This case is for methods that send a JSON request body, such as creating a resource.
+            // The syntax looks like: `+> <method name> [<route path>] -> <builder name> (<body name>: <body type>)`
+            $(+> $fn3:ident [$($p3:literal)?] -> $t3:ident ($b3:ident: $bt3:ty))?
 
-            $(?> $fn3:ident [$q3:literal] -> $n3:ident: $t3:ty)?
+            // Case 4
+            // This case is for methods that need a route variable and send a JSON request body,
+            // such as updating a resource by id.
+            // The syntax looks like: `#> <method name> [<route path>] -> <builder name> = <path variable name>: <id type> (<body name>: <body type>)`
+            $(#> $fn4:ident [$($p4:literal)?] -> $t4:ident = $e4:ident $(: $et4:ident)? ($b4:ident: $bt4:ty))?
+
+            // Case 5
+            // This case is for methods that set a query parameter on the current builder.
+            // The syntax looks like: `?> <method name> [<query key>] -> <value name>: <value type>`
+            $(?> $fn5:ident [$q5:literal] -> $n5:ident: $t5:ty)?
         ),*
     )+)=> (
         $(paste! {
@@ -279,22 +288,53 @@ macro_rules! impl_builder {
                 $(#[$doc])*
                 // Case 1
                 $(
+                    #[allow(unused_mut)]
                     pub fn $fn1(mut self) -> [<$t1 Builder>] {
-                        join_path!(self, &[$($p1)?]);
+                        // `build` returns any error, as this method can't.
+                        $(let _ = join_path!(self, &[$p1]);)?
                         self.into()
                     }
                 )?
                 // Case 2
                 $(
-                    pub fn $fn2<T: ToString>(mut self, $e2: T) -> [<$t2 Builder>] {
-                        join_path!(self, &[$($p2,)? &$e2.to_string()]);
-                        self.into()
+                    pub fn $fn2<T: Into<route_var!($($et2)?)>>(mut self, $e2: T) -> Result<[<$t2 Builder>], $crate::error::BuilderError> {
+                        let $e2: route_var!($($et2)?) = $e2.into();
+                        $crate::builder::RouteVar::validate_route_var(&$e2, stringify!($e2))?;
+                        join_path!(
+                            self,
+                            &[$($p2,)? $e2.as_str()],
+                            &[$($p2,)? concat!(":", stringify!($e2))]
+                        )?;
+                        Ok(self.into())
                     }
                 )?
                 // Case 3
                 $(
-                    pub fn $fn3(mut self, $n3: $t3) -> [<$i Builder>] {
-                        join_query!(self, $q3, $n3);
+                    #[allow(unused_mut)]
+                    pub fn $fn3(mut self, $b3: &$bt3) -> Result<[<$t3 Builder>], $crate::error::BuilderError> {
+                        $(join_path!(self, &[$p3])?;)?
+                        join_body!(self, $b3);
+                        Ok(self.into())
+                    }
+                )?
+                // Case 4
+                $(
+                    pub fn $fn4<T: Into<route_var!($($et4)?)>>(mut self, $e4: T, $b4: &$bt4) -> Result<[<$t4 Builder>], $crate::error::BuilderError> {
+                        let $e4: route_var!($($et4)?) = $e4.into();
+                        $crate::builder::RouteVar::validate_route_var(&$e4, stringify!($e4))?;
+                        join_path!(
+                            self,
+                            &[$($p4,)? $e4.as_str()],
+                            &[$($p4,)? concat!(":", stringify!($e4))]
+                        )?;
+                        join_body!(self, $b4);
+                        Ok(self.into())
+                    }
+                )?
+                // Case 5
+                $(
+                    pub fn $fn5(mut self, $n5: $t5) -> [<$i Builder>] {
+                        join_query!(self, $q5, $n5);
                         self.into()
                     }
                 )?
@@ -315,15 +355,11 @@ macro_rules! impl_client {
                 $(
                     pub fn $fn(&self) -> [<$t Builder>] {
                         let mut b = [<$t Builder>] {
-                            builder: self.new_request().map(|r| RwLock::new(Builder{
-                                query: HashMap::new(),
-                                url: self.url.clone(),
-                                req: r,
-
-                            })),
+                            builder: Builder::new(self.url.clone(), self.new_base_request()),
                             client: Arc::clone(&self.client),
                         };
-                        join_path!(b, &[$p]);
+                        // `build` returns any error, as this method can't.
+                        let _ = join_path!(b, &[$p]);
                         b
                     }
                 )?
@@ -332,39 +368,79 @@ macro_rules! impl_client {
     );
 }
 
+/// Resolves the type of a route variable in `impl_builder!`. Route variables without an id type
+/// accept any string.
+macro_rules! route_var {
+    () => {
+        String
+    };
+    ($t: ident) => {
+        $crate::models::$t
+    };
+}
+
+/// Appends path segments to a builder's URL. Route template segments (such as `:id` in place of
+/// a route variable) may be given separately, otherwise the path segments are used. Evaluates to
+/// a `Result`, which is an error if the URL can't have path segments.
 macro_rules! join_path {
     ($e: ident, $p: expr) => {
-        if $e.builder.is_ok() {
-            // We've checked that this works
-            let inner = $e.builder.unwrap();
-            inner
-                .write()
-                .unwrap()
-                .url
-                .path_segments_mut()
-                .unwrap()
-                .extend($p);
-
-            $e.builder = Ok(inner);
-        }
+        join_path!($e, $p, $p)
+    };
+    ($e: ident, $p: expr, $r: expr) => {
+        $e.builder.join_path($p, $r)
     };
 }
 
+/// Sets a query parameter on a builder, replacing any previous value.
 macro_rules! join_query {
     ($e: ident, $k: expr, $v: expr) => {
-        if $e.builder.is_ok() {
-            // We've checked that this works
-            let inner = $e.builder.unwrap();
-            inner.write().unwrap().query.insert($k, $v.to_string());
+        $e.builder.join_query($k, $v.to_string())
+    };
+}
 
-            $e.builder = Ok(inner);
-        }
+/// Sets a builder's JSON request body, returning early from the enclosing function if the body
+/// can't be serialized.
+macro_rules! join_body {
+    ($e: ident, $b: expr) => {
+        $e.builder.join_body($b)?
     };
 }
 
+/// Implements conversions and validation for an id newtype. Each id names a validation rule from
+/// `crate::builder::validate`, which is checked before the id is used in a route.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// pub struct UserId(pub String);
+/// id_string!(UserId => object_id);
+/// ```
 macro_rules! id_string {
-    ($($name:ident),*) => {
+    ($($name:ident => $rule:ident),*) => {
         $(
+            impl $name {
+                pub fn as_str(&self) -> &str {
+                    &self.0
+                }
+
+                /// Checks that the id is well formed, so it can't change the route it's used in.
+                pub fn validate(&self) -> Result<(), $crate::error::BuilderError> {
+                    $crate::builder::validate::$rule(stringify!($name), &self.0)
+                }
+            }
+
+            impl $crate::builder::RouteVar for $name {
+                fn validate_route_var(&self, _: &'static str) -> Result<(), $crate::error::BuilderError> {
+                    self.validate()
+                }
+            }
+
+            impl std::fmt::Display for $name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(&self.0)
+                }
+            }
+
             impl std::ops::Deref for $name {
                 type Target = String;
 
@@ -384,6 +460,205 @@ macro_rules! id_string {
                     Self(i.to_string())
                 }
             }
+
+            impl std::convert::From<&String> for $name {
+                fn from(i: &String) -> Self {
+                    Self(i.clone())
+                }
+            }
+
+            impl std::convert::From<&$name> for $name {
+                fn from(i: &$name) -> Self {
+                    i.clone()
+                }
+            }
+
+            impl std::convert::From<$name> for String {
+                fn from(i: $name) -> Self {
+                    i.0
+                }
+            }
+
+            impl PartialEq<str> for $name {
+                fn eq(&self, other: &str) -> bool {
+                    self.0 == other
+                }
+            }
+
+            impl PartialEq<&str> for $name {
+                fn eq(&self, other: &&str) -> bool {
+                    self.0 == *other
+                }
+            }
         )*
     }
 }
+
+/// Defines an enum for a server-defined string value. Values that aren't known to this version of
+/// the crate are kept in an `Unknown` variant instead of failing deserialization, so the
+/// enums can't be `Copy`. `Display` and `FromStr` use the same string values as serde.
+/// Documentation is passed through.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// string_enum!(
+///     /// Documentation is passed through!
+///     pub enum SiteRole {
+///         Admin = "site-admin",
+///         Member = "site-member",
+///     }
+/// );
+///
+/// // Expands to ...
+///
+/// /// Documentation is passed through!
+/// pub enum SiteRole {
+///     Admin,
+///     Member,
+///     Unknown(String),
+/// }
+/// ```
+macro_rules! string_enum {
+    (
+        $(#[$doc:meta])*
+        pub enum $name:ident {
+            $(
+                $(#[$vdoc:meta])*
+                $variant:ident = $value:literal
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $(
+                $(#[$vdoc])*
+                $variant,
+            )*
+            /// A value returned by the server that isn't known to this version of the crate.
+            Unknown(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(s) => s,
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl std::convert::From<String> for $name {
+            fn from(s: String) -> Self {
+                match s.as_str() {
+                    $($value => $name::$variant,)*
+                    _ => $name::Unknown(s),
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = std::convert::Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(s.to_string().into())
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                String::deserialize(deserializer).map(Self::from)
+            }
+        }
+    };
+}
+
+/// Defines a partial-update type for a model, where every field is optional and unset fields are
+/// skipped when serializing. A `diff` constructor computes the patch needed to turn one value of
+/// the model into another. Documentation is passed through.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// patch!(
+///     /// Documentation is passed through!
+///     User => UserPatch {
+///         name: String,
+///         /// Field attributes are passed through too, but must also support `Option<T>`.
+///         #[serde(rename = "user_roles")]
+///         roles: Vec<SiteRole>,
+///     }
+/// );
+///
+/// // Expands to ...
+///
+/// /// Documentation is passed through!
+/// pub struct UserPatch {
+///     pub name: Option<String>,
+///     pub roles: Option<Vec<SiteRole>>,
+/// }
+///
+/// impl UserPatch {
+///     pub fn diff(old: &User, new: &User) -> Self { ... }
+///     pub fn is_empty(&self) -> bool { ... }
+/// }
+/// ```
+macro_rules! patch {
+    (
+        $(#[$doc:meta])*
+        $model:ident => $name:ident {
+            $(
+                $(#[$attr:meta])*
+                $field:ident: $t:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+        pub struct $name {
+            $(
+                $(#[$attr])*
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                pub $field: Option<$t>,
+            )*
+        }
+
+        impl $name {
+            /// Returns a patch containing only the fields that differ between `old` and `new`,
+            /// set to their values in `new`.
+            pub fn diff(old: &$model, new: &$model) -> Self {
+                Self {
+                    $($field: if old.$field != new.$field {
+                        Some(new.$field.clone())
+                    } else {
+                        None
+                    },)*
+                }
+            }
+
+            /// Returns true if no fields are set.
+            pub fn is_empty(&self) -> bool {
+                true $(&& self.$field.is_none())*
+            }
+        }
+    };
+}
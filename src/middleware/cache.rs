@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use http::header::{self, HeaderMap, HeaderValue};
+use http::{Method, Request, Response, StatusCode};
+
+use super::{credentials_digest, request_key, Layer};
+use crate::error::Error;
+use crate::transport::{Bytes, Transport};
+
+/// A cached response body along with the headers and validators it was returned with.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    /// The `ETag` header, sent back as `If-None-Match` when revalidating.
+    pub etag: Option<HeaderValue>,
+    /// The `Last-Modified` header, sent back as `If-Modified-Since` when revalidating.
+    pub last_modified: Option<HeaderValue>,
+    /// When the response was stored or last revalidated.
+    pub stored_at: SystemTime,
+}
+
+impl CachedResponse {
+    fn from_response(res: &Response<Bytes>) -> Self {
+        Self {
+            status: res.status(),
+            headers: res.headers().clone(),
+            body: res.body().clone(),
+            etag: res.headers().get(header::ETAG).cloned(),
+            last_modified: res.headers().get(header::LAST_MODIFIED).cloned(),
+            stored_at: SystemTime::now(),
+        }
+    }
+
+    fn has_validators(&self) -> bool {
+        self.etag.is_some() || self.last_modified.is_some()
+    }
+
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        self.stored_at
+            .elapsed()
+            .map(|age| age < ttl)
+            .unwrap_or(false)
+    }
+
+    fn to_response(&self) -> Result<Response<Bytes>, Error> {
+        let mut res = Response::builder()
+            .status(self.status)
+            .body(self.body.clone())?;
+        *res.headers_mut() = self.headers.clone();
+        Ok(res)
+    }
+}
+
+/// Storage for cached responses. Keys identify a URL and the credentials it was requested with.
+#[async_trait]
+pub trait CacheStore: Send + Sync {
+    async fn get(&self, key: &str) -> Option<CachedResponse>;
+    async fn put(&self, key: &str, res: CachedResponse);
+    async fn remove(&self, key: &str);
+
+    /// Removes every response whose key starts with `prefix`. Used to evict list queries with a
+    /// query string after a mutation. The default implementation does nothing, so stores that
+    /// can't enumerate their keys only evict exact URLs.
+    async fn remove_prefix(&self, prefix: &str) {
+        let _ = prefix;
+    }
+}
+
+#[async_trait]
+impl<T: CacheStore + ?Sized> CacheStore for Arc<T> {
+    async fn get(&self, key: &str) -> Option<CachedResponse> {
+        (**self).get(key).await
+    }
+
+    async fn put(&self, key: &str, res: CachedResponse) {
+        (**self).put(key, res).await
+    }
+
+    async fn remove(&self, key: &str) {
+        (**self).remove(key).await
+    }
+
+    async fn remove_prefix(&self, prefix: &str) {
+        (**self).remove_prefix(prefix).await
+    }
+}
+
+/// An in-memory [`CacheStore`].
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes every cached response.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl CacheStore for MemoryStore {
+    async fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    async fn put(&self, key: &str, res: CachedResponse) {
+        self.entries.lock().unwrap().insert(key.to_string(), res);
+    }
+
+    async fn remove(&self, key: &str) {
+        self.entries.lock().unwrap().remove(key);
+    }
+
+    async fn remove_prefix(&self, prefix: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|key, _| !key.starts_with(prefix));
+    }
+}
+
+/// Caches successful `GET` responses.
+///
+/// By default cached responses are revalidated on every request by sending `If-None-Match` and
+/// `If-Modified-Since`, and a `304 Not Modified` response is answered from the cache. Responses
+/// without an `ETag` or `Last-Modified` header are only cached when a [`ttl`](Self::ttl) is set.
+/// Responses are never cached when the manager sends `Cache-Control: no-store`.
+///
+/// Successful `POST`, `PUT`, `PATCH` and `DELETE` requests evict the cached responses for the same
+/// URL and for each of its parent paths, with any query string. For example deleting
+/// `/api/environments/:id/devurls/:id` evicts that dev URL and the `/api/environments/:id/devurls`
+/// list. Lists of the same resource under other paths aren't evicted: deleting
+/// `/api/environments/:id` leaves `/api/orgs/:id/environments` cached until it's revalidated or
+/// its TTL expires.
+#[derive(Clone)]
+pub struct CacheLayer {
+    store: Arc<dyn CacheStore>,
+    ttl: Option<Duration>,
+}
+
+impl CacheLayer {
+    pub fn new<S: CacheStore + 'static>(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            ttl: None,
+        }
+    }
+
+    /// Serves cached responses for `ttl` without contacting the manager. Once expired, responses
+    /// are revalidated if they have validators and fetched again otherwise.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+}
+
+impl<S: Transport> Layer<S> for CacheLayer {
+    type Service = Cache<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Cache {
+            inner,
+            config: self.clone(),
+        }
+    }
+}
+
+/// A transport created by [`CacheLayer`].
+pub struct Cache<S> {
+    inner: S,
+    config: CacheLayer,
+}
+
+fn is_no_store(res: &Response<Bytes>) -> bool {
+    res.headers()
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| {
+            v.split(',')
+                .any(|d| d.trim().eq_ignore_ascii_case("no-store"))
+        })
+}
+
+/// Returns the keys evicted by a mutation: its own URL and each of its parent paths, without a
+/// query string.
+fn eviction_keys(req: &Request<Bytes>) -> Vec<String> {
+    let digest = credentials_digest(req);
+    let uri = req.uri();
+    let base = match (uri.scheme_str(), uri.authority()) {
+        (Some(scheme), Some(authority)) => format!("{}://{}", scheme, authority),
+        _ => String::new(),
+    };
+
+    let mut path = uri.path().trim_end_matches('/');
+    let mut keys = Vec::new();
+    while !path.is_empty() {
+        keys.push(format!("{}:{}{}", digest, base, path));
+        path = &path[..path.rfind('/').unwrap_or(0)];
+    }
+    keys
+}
+
+#[async_trait]
+impl<S: Transport> Transport for Cache<S> {
+    async fn send(&self, mut req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+        let key = request_key(&req);
+        let store = &self.config.store;
+
+        if req.method() != Method::GET {
+            let evict = eviction_keys(&req);
+            let res = self.inner.send(req).await?;
+            if res.status().is_success() {
+                for key in &evict {
+                    store.remove(key).await;
+                    store.remove_prefix(&format!("{}?", key)).await;
+                }
+            }
+            return Ok(res);
+        }
+
+        let cached = store.get(&key).await;
+        if let Some(cached) = &cached {
+            if let Some(ttl) = self.config.ttl {
+                if cached.is_fresh(ttl) {
+                    return cached.to_response();
+                }
+            }
+            if let Some(etag) = &cached.etag {
+                req.headers_mut()
+                    .insert(header::IF_NONE_MATCH, etag.clone());
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                req.headers_mut()
+                    .insert(header::IF_MODIFIED_SINCE, last_modified.clone());
+            }
+        }
+
+        let res = self.inner.send(req).await?;
+        match cached {
+            Some(mut cached) if res.status() == StatusCode::NOT_MODIFIED => {
+                cached.stored_at = SystemTime::now();
+                let res = cached.to_response();
+                store.put(&key, cached).await;
+                res
+            }
+            _ => {
+                if res.status().is_success() && !is_no_store(&res) {
+                    let new = CachedResponse::from_response(&res);
+                    if new.has_validators() || self.config.ttl.is_some() {
+                        store.put(&key, new).await;
+                    }
+                }
+                Ok(res)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::Executor;
+    use crate::Coder;
+
+    /// A transport that serves a fixed body with an `ETag`, answering `304` when the client sends
+    /// the current `ETag` back.
+    struct EtagTransport {
+        etag: Option<&'static str>,
+        calls: Mutex<Vec<StatusCode>>,
+    }
+
+    #[async_trait]
+    impl Transport for EtagTransport {
+        async fn send(&self, req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+            let mut res = Response::builder();
+            if let Some(etag) = self.etag {
+                res = res.header(header::ETAG, etag);
+            }
+            let res = match (self.etag, req.headers().get(header::IF_NONE_MATCH)) {
+                (Some(etag), Some(sent)) if sent == etag => {
+                    res.status(StatusCode::NOT_MODIFIED).body(Bytes::new())?
+                }
+                _ => res
+                    .status(StatusCode::OK)
+                    .body(Bytes::from_static(br#"["default"]"#))?,
+            };
+            self.calls.lock().unwrap().push(res.status());
+            Ok(res)
+        }
+    }
+
+    fn client(etag: Option<&'static str>, layer: CacheLayer) -> (Coder, Arc<EtagTransport>) {
+        let t = Arc::new(EtagTransport {
+            etag,
+            calls: Mutex::new(Vec::new()),
+        });
+        let c = Coder::with_transport("https://coder.example.com".into(), "key", t.clone())
+            .unwrap()
+            .layer(layer);
+        (c, t)
+    }
+
+    #[tokio::test]
+    async fn test_cache_revalidates() {
+        let store = Arc::new(MemoryStore::new());
+        let (c, t) = client(Some("\"v1\""), CacheLayer::new(store.clone()));
+
+        for _ in 0..2 {
+            let res = c.orgs().namespaces().execute().await.expect("send request");
+            assert_eq!(res.status_code, StatusCode::OK);
+            assert_eq!(res.response.unwrap(), vec!["default".to_string()]);
+        }
+
+        assert_eq!(
+            *t.calls.lock().unwrap(),
+            vec![StatusCode::OK, StatusCode::NOT_MODIFIED]
+        );
+        assert_eq!(store.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cache_without_validators() {
+        let (c, t) = client(None, CacheLayer::new(MemoryStore::new()));
+        for _ in 0..2 {
+            c.orgs().namespaces().execute().await.expect("send request");
+        }
+        assert_eq!(t.calls.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cache_evicts_parents() {
+        let t = crate::transport::test::MockTransport::new(200, "[]");
+        let store = Arc::new(MemoryStore::new());
+        let c = Coder::with_transport("https://coder.example.com".into(), "key", t.clone())
+            .unwrap()
+            .layer(CacheLayer::new(store.clone()).ttl(Duration::from_secs(60)));
+        let get = |path| c.request(Method::GET, path).unwrap();
+
+        let devurls = get("/environments/e1/devurls");
+        for req in &[
+            devurls.clone(),
+            get("/environments").query("user_id", "u1"),
+            get("/orgs/default/environments"),
+        ] {
+            req.send_raw().await.expect("send request");
+        }
+        assert_eq!(store.len(), 3);
+
+        c.request(Method::DELETE, "/environments/e1/devurls/d1")
+            .unwrap()
+            .send_raw()
+            .await
+            .expect("send request");
+
+        // Lists under the deleted dev URL's path were evicted, including ones with a query.
+        assert_eq!(store.len(), 1);
+        devurls.send_raw().await.expect("send request");
+        get("/orgs/default/environments")
+            .send_raw()
+            .await
+            .expect("send request");
+        assert_eq!(t.requests.lock().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_eviction_keys() {
+        let req = Request::delete("https://coder.example.com/api/environments/e1/devurls/d1")
+            .header("Session-Token", "key")
+            .body(Bytes::new())
+            .unwrap();
+        let keys: Vec<_> = eviction_keys(&req)
+            .into_iter()
+            .map(|k| k.split_once(':').unwrap().1.to_string())
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "https://coder.example.com/api/environments/e1/devurls/d1",
+                "https://coder.example.com/api/environments/e1/devurls",
+                "https://coder.example.com/api/environments/e1",
+                "https://coder.example.com/api/environments",
+                "https://coder.example.com/api",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_ttl() {
+        let (c, t) = client(
+            None,
+            CacheLayer::new(MemoryStore::new()).ttl(Duration::from_secs(60)),
+        );
+        for _ in 0..3 {
+            let res = c.orgs().namespaces().execute().await.expect("send request");
+            assert_eq!(res.response.unwrap(), vec!["default".to_string()]);
+        }
+        assert_eq!(t.calls.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use http::{Method, Request, Response};
+use tokio::sync::oneshot;
+
+use sha2::{Digest, Sha256};
+
+use super::{clone_response, Layer};
+use crate::error::Error;
+use crate::transport::{Bytes, Transport};
+
+type Shared = Result<Response<Bytes>, Arc<Error>>;
+type InFlight = Mutex<HashMap<String, Vec<oneshot::Sender<Shared>>>>;
+
+/// Shares a single request between identical `GET` requests that are in flight at the same time.
+/// Requests are identical when they have the same URL and headers, including the session token.
+/// Each caller receives its own copy of the response, so each deserializes the same result.
+///
+/// Failed requests return [`Error::Transport`] to every caller, with the original error as its
+/// [`source`](std::error::Error::source), whether or not the request was shared.
+#[derive(Debug, Clone, Default)]
+pub struct CoalesceLayer;
+
+impl CoalesceLayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S: Transport> Layer<S> for CoalesceLayer {
+    type Service = Coalesce<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Coalesce {
+            inner,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// A transport created by [`CoalesceLayer`].
+pub struct Coalesce<S> {
+    inner: S,
+    in_flight: InFlight,
+}
+
+/// An error returned to every caller sharing a failed request.
+#[derive(Debug)]
+struct SharedError(Arc<Error>);
+
+impl fmt::Display for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for SharedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.0)
+    }
+}
+
+impl From<SharedError> for Error {
+    fn from(err: SharedError) -> Self {
+        Error::Transport(Box::new(err))
+    }
+}
+
+/// Returns a key identifying a request's URL and all of its headers. Header values are hashed, so
+/// the session token isn't kept in the key.
+fn coalesce_key(req: &Request<Bytes>) -> String {
+    let mut headers: Vec<_> = req
+        .headers()
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_bytes()))
+        .collect();
+    headers.sort_unstable();
+
+    let mut hasher = Sha256::new();
+    for (name, value) in headers {
+        hasher.update(name);
+        hasher.update(b":");
+        hasher.update(value);
+        hasher.update(b"\n");
+    }
+    format!("{:x}:{}", hasher.finalize(), req.uri())
+}
+
+/// Owns an in-flight entry while its request is sent. If the request is cancelled the entry is
+/// removed, which wakes the waiting callers so they can send the request themselves.
+struct Leader<'a> {
+    in_flight: &'a InFlight,
+    key: Option<String>,
+}
+
+impl Leader<'_> {
+    fn finish(mut self) -> Vec<oneshot::Sender<Shared>> {
+        let key = self.key.take().unwrap();
+        self.in_flight
+            .lock()
+            .unwrap()
+            .remove(&key)
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.in_flight.lock().unwrap().remove(&key);
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Transport> Transport for Coalesce<S> {
+    async fn send(&self, req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+        if req.method() != Method::GET {
+            return self.inner.send(req).await;
+        }
+
+        let key = coalesce_key(&req);
+        let waiting = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get_mut(&key) {
+                Some(waiters) => {
+                    let (tx, rx) = oneshot::channel();
+                    waiters.push(tx);
+                    Some(rx)
+                }
+                None => {
+                    in_flight.insert(key.clone(), Vec::new());
+                    None
+                }
+            }
+        };
+
+        if let Some(rx) = waiting {
+            return match rx.await {
+                Ok(Ok(res)) => Ok(res),
+                Ok(Err(err)) => Err(SharedError(err).into()),
+                // The shared request was cancelled.
+                Err(_) => self.inner.send(req).await,
+            };
+        }
+
+        let leader = Leader {
+            in_flight: &self.in_flight,
+            key: Some(key),
+        };
+        let res = self.inner.send(req).await;
+        let waiters = leader.finish();
+
+        match res {
+            Ok(res) => {
+                // Waiters whose callers were dropped are ignored.
+                for tx in waiters {
+                    let _ = tx.send(Ok(clone_response(&res)));
+                }
+                Ok(res)
+            }
+            Err(err) => {
+                let err = Arc::new(err);
+                for tx in waiters {
+                    let _ = tx.send(Err(Arc::clone(&err)));
+                }
+                Err(SharedError(err).into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::client::Executor;
+    use crate::Coder;
+
+    /// A transport that answers every request after a short delay and counts requests.
+    #[derive(Default)]
+    struct SlowTransport {
+        calls: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl Transport for SlowTransport {
+        async fn send(&self, _req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+            *self.calls.lock().unwrap() += 1;
+            tokio::time::delay_for(Duration::from_millis(50)).await;
+            Ok(Response::new(Bytes::from_static(br#"["default"]"#)))
+        }
+    }
+
+    fn client(token: &str, t: Arc<SlowTransport>) -> Coder {
+        Coder::with_transport("https://coder.example.com".into(), token, t)
+            .unwrap()
+            .layer(CoalesceLayer::new())
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_identical_gets() {
+        let t = Arc::new(SlowTransport::default());
+        let c = client("key", t.clone());
+
+        let q = c.orgs().namespaces();
+        let (a, b, d) = tokio::join!(q.execute(), q.execute(), q.execute());
+        for res in [a, b, d] {
+            let res = res.expect("send request").response.unwrap();
+            assert_eq!(res, vec!["default".to_string()]);
+        }
+        assert_eq!(*t.calls.lock().unwrap(), 1);
+
+        // Requests that aren't in flight at the same time aren't shared.
+        c.orgs().namespaces().execute().await.expect("send request");
+        assert_eq!(*t.calls.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_distinct_credentials() {
+        let t = Arc::new(SlowTransport::default());
+        let a = client("a", t.clone());
+        let b = client("b", t.clone());
+
+        let (a, b) = (a.orgs().namespaces(), b.orgs().namespaces());
+        let (a, b) = tokio::join!(a.execute(), b.execute());
+        a.expect("send request");
+        b.expect("send request");
+        assert_eq!(*t.calls.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_distinct_headers() {
+        let t = Arc::new(SlowTransport::default());
+        let c = client("key", t.clone());
+
+        let json = c.request(Method::GET, "/orgs/namespaces").unwrap();
+        let text = json.clone().header("Accept", "text/plain").unwrap();
+        let (a, b, d) = tokio::join!(json.send_raw(), json.send_raw(), text.send_raw());
+        for res in [a, b, d] {
+            res.expect("send request");
+        }
+        assert_eq!(*t.calls.lock().unwrap(), 2);
+    }
+
+    /// A transport that fails every request after a short delay.
+    struct FailingTransport;
+
+    #[async_trait]
+    impl Transport for FailingTransport {
+        async fn send(&self, _req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+            tokio::time::delay_for(Duration::from_millis(50)).await;
+            Err(Error::RuntimeShutdown)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_error_shape() {
+        let c = Coder::with_transport("https://coder.example.com".into(), "key", FailingTransport)
+            .unwrap()
+            .layer(CoalesceLayer::new());
+
+        let q = c.orgs().namespaces();
+        let (a, b) = tokio::join!(q.execute(), q.execute());
+        let alone = q.execute().await;
+        for res in [a, b, alone] {
+            match res {
+                Err(Error::Transport(err)) => {
+                    let source = err.source().unwrap().downcast_ref::<Error>();
+                    assert!(matches!(source, Some(Error::RuntimeShutdown)), "{:?}", err);
+                }
+                res => panic!("unexpected result {:?}", res.map(|r| r.status_code)),
+            }
+        }
+    }
+}
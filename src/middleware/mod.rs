@@ -0,0 +1,265 @@
+//! Middleware for inspecting and modifying requests and responses.
+//!
+//! Middleware are [`tower::Layer`](https://docs.rs/tower/latest/tower/trait.Layer.html)s that wrap
+//! a [`Transport`] in another `Transport`, so each layer sees the outgoing `http::Request` and the
+//! incoming response. Layers are added to a client with [`Coder::layer`], and the most recently
+//! added layer is the outermost one.
+//!
+//! With the `tracing` feature, `TraceLayer` opens a span for the requests passing through its
+//! place in the chain.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//!
+//! use coder::middleware::{self, RateLimitLayer, RetryLayer};
+//! use coder::Coder;
+//!
+//! let c = Coder::new("https://coder.example.com".into(), "api key")?
+//!     .layer(RetryLayer::new(3))
+//!     .layer(RateLimitLayer::new(10, Duration::from_secs(1)))
+//!     .layer(middleware::from_fn(|mut req, next| async move {
+//!         req.headers_mut()
+//!             .insert("X-Team", http::HeaderValue::from_static("platform"));
+//!         next.run(req).await
+//!     }));
+//! # Ok::<(), coder::Error>(())
+//! ```
+
+mod cache;
+mod coalesce;
+mod rate_limit;
+mod retry;
+#[cfg(feature = "tracing")]
+mod trace;
+
+pub use cache::{Cache, CacheLayer, CacheStore, CachedResponse, MemoryStore};
+pub use coalesce::{Coalesce, CoalesceLayer};
+pub use rate_limit::{RateLimit, RateLimitLayer};
+pub use retry::{Retry, RetryLayer};
+pub use tower_layer::Layer;
+#[cfg(feature = "tracing")]
+pub use trace::{Trace, TraceLayer};
+
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use http::{HeaderValue, Request, Response};
+use sha2::{Digest, Sha256};
+
+use crate::client::{Coder, Route};
+use crate::error::Error;
+use crate::transport::{Bytes, Transport};
+
+impl Coder {
+    /// Wraps the client's transport with a middleware layer. The most recently added layer sees
+    /// requests first and responses last.
+    pub fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Arc<dyn Transport>>,
+        L::Service: Transport + 'static,
+    {
+        self.client = Arc::new(layer.layer(self.client));
+        self
+    }
+}
+
+/// The rest of the middleware chain, passed to middleware created with [`from_fn`].
+#[derive(Clone)]
+pub struct Next {
+    inner: Arc<dyn Transport>,
+}
+
+impl Next {
+    /// Sends the request through the rest of the chain.
+    pub async fn run(self, req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+        self.inner.send(req).await
+    }
+}
+
+/// Creates a middleware layer from an async function, which receives each request and the rest
+/// of the chain.
+pub fn from_fn<F, Fut>(f: F) -> FromFnLayer<F>
+where
+    F: Fn(Request<Bytes>, Next) -> Fut + Send + Sync + Clone + 'static,
+    Fut: Future<Output = Result<Response<Bytes>, Error>> + Send + 'static,
+{
+    FromFnLayer { f }
+}
+
+/// A layer created by [`from_fn`].
+#[derive(Clone)]
+pub struct FromFnLayer<F> {
+    f: F,
+}
+
+impl<S, F> Layer<S> for FromFnLayer<F>
+where
+    S: Transport + 'static,
+    F: Clone,
+{
+    type Service = FromFn<F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FromFn {
+            f: self.f.clone(),
+            next: Next {
+                inner: Arc::new(inner),
+            },
+        }
+    }
+}
+
+/// A transport created by [`FromFnLayer`].
+pub struct FromFn<F> {
+    f: F,
+    next: Next,
+}
+
+#[async_trait]
+impl<F, Fut> Transport for FromFn<F>
+where
+    F: Fn(Request<Bytes>, Next) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Response<Bytes>, Error>> + Send + 'static,
+{
+    async fn send(&self, req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+        (self.f)(req, self.next.clone()).await
+    }
+}
+
+/// Copies a request so it can be sent more than once. `http::Request` can't be cloned because of
+/// its extensions, so only extensions known to this crate are copied.
+pub(crate) fn clone_request(req: &Request<Bytes>) -> Request<Bytes> {
+    let mut new = Request::new(req.body().clone());
+    *new.method_mut() = req.method().clone();
+    *new.uri_mut() = req.uri().clone();
+    *new.version_mut() = req.version();
+    *new.headers_mut() = req.headers().clone();
+    if let Some(route) = req.extensions().get::<Route>() {
+        new.extensions_mut().insert(route.clone());
+    }
+    new
+}
+
+/// Returns a key identifying a request's URL and the credentials it was sent with, used to share
+/// responses between identical requests. The key starts with a SHA-256 digest of the session
+/// token, so the token isn't kept in the key and keys are stable across builds.
+pub(crate) fn request_key(req: &Request<Bytes>) -> String {
+    format!("{}:{}", credentials_digest(req), req.uri())
+}
+
+/// Returns the hex encoded SHA-256 digest of a request's session token.
+pub(crate) fn credentials_digest(req: &Request<Bytes>) -> String {
+    let token = req
+        .headers()
+        .get("Session-Token")
+        .map(HeaderValue::as_bytes)
+        .unwrap_or_default();
+    Sha256::digest(token)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Copies a response so it can be returned to more than one caller.
+pub(crate) fn clone_response(res: &Response<Bytes>) -> Response<Bytes> {
+    let mut new = Response::new(res.body().clone());
+    *new.status_mut() = res.status();
+    *new.version_mut() = res.version();
+    *new.headers_mut() = res.headers().clone();
+    new
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    #[cfg(feature = "tracing")]
+    pub(crate) use super::trace::test as trace;
+
+    use super::*;
+    use crate::client::Executor;
+    use crate::transport::test::MockTransport;
+
+    /// A transport that replies with a sequence of statuses, repeating the last one.
+    pub(crate) struct SequenceTransport {
+        statuses: Mutex<VecDeque<u16>>,
+        pub(crate) calls: Mutex<usize>,
+    }
+
+    impl SequenceTransport {
+        pub(crate) fn new(statuses: &[u16]) -> Arc<Self> {
+            Arc::new(Self {
+                statuses: Mutex::new(statuses.iter().copied().collect()),
+                calls: Mutex::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Transport for SequenceTransport {
+        async fn send(&self, _req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+            *self.calls.lock().unwrap() += 1;
+            let mut statuses = self.statuses.lock().unwrap();
+            let status = if statuses.len() > 1 {
+                statuses.pop_front().unwrap()
+            } else {
+                statuses[0]
+            };
+            let body = if status < 400 {
+                "[]"
+            } else {
+                r#"{"error":{"msg":"unavailable"}}"#
+            };
+            Ok(Response::builder()
+                .status(status)
+                .body(Bytes::from_static(body.as_bytes()))?)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_from_fn_layer_order() {
+        let t = MockTransport::new(200, "[]");
+        let c = Coder::with_transport("https://coder.example.com".into(), "key", t.clone())
+            .unwrap()
+            .layer(from_fn(|mut req: Request<Bytes>, next: Next| async move {
+                req.headers_mut()
+                    .append("X-Order", "inner".parse().unwrap());
+                next.run(req).await
+            }))
+            .layer(from_fn(|mut req: Request<Bytes>, next: Next| async move {
+                req.headers_mut()
+                    .append("X-Order", "outer".parse().unwrap());
+                let mut res = next.run(req).await?;
+                res.headers_mut().insert("X-Seen", "true".parse().unwrap());
+                Ok(res)
+            }));
+
+        let res = c.orgs().namespaces().execute().await.expect("send request");
+        assert_eq!(res.headers["X-Seen"], "true");
+
+        let reqs = t.requests.lock().unwrap();
+        let order: Vec<_> = reqs[0].headers().get_all("X-Order").iter().collect();
+        assert_eq!(order, vec!["outer", "inner"]);
+    }
+
+    #[test]
+    fn test_request_key_credentials() {
+        let req = |token: &'static str| {
+            Request::builder()
+                .uri("https://coder.example.com/api/users/me")
+                .header("Session-Token", token)
+                .body(Bytes::new())
+                .unwrap()
+        };
+        assert_eq!(request_key(&req("a")), request_key(&req("a")));
+        assert_ne!(request_key(&req("a")), request_key(&req("b")));
+        assert!(!request_key(&req("secret")).contains("secret"));
+        assert_eq!(
+            request_key(&req("key")),
+            "2c70e12b7a0646f92279f427c7b38e7334d8e5389cff167a1dc30e73f826b683:\
+             https://coder.example.com/api/users/me"
+        );
+    }
+}
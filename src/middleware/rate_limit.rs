@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use http::{Request, Response};
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use super::Layer;
+use crate::error::Error;
+use crate::transport::{Bytes, Transport};
+
+/// Limits requests to `num` per `per` interval. Requests over the limit wait for the next
+/// interval instead of failing.
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    num: u64,
+    per: Duration,
+}
+
+impl RateLimitLayer {
+    pub fn new(num: u64, per: Duration) -> Self {
+        Self { num, per }
+    }
+}
+
+impl<S: Transport> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            num: self.num.max(1),
+            per: self.per,
+            window: Mutex::new(Window {
+                start: Instant::now(),
+                used: 0,
+            }),
+        }
+    }
+}
+
+struct Window {
+    start: Instant,
+    used: u64,
+}
+
+/// A transport created by [`RateLimitLayer`].
+pub struct RateLimit<S> {
+    inner: S,
+    num: u64,
+    per: Duration,
+    window: Mutex<Window>,
+}
+
+#[async_trait]
+impl<S: Transport> Transport for RateLimit<S> {
+    async fn send(&self, req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+        {
+            let mut window = self.window.lock().await;
+            let now = Instant::now();
+            if now >= window.start + self.per {
+                window.start = now;
+                window.used = 0;
+            }
+            if window.used >= self.num {
+                // Holding the lock while waiting keeps requests in arrival order.
+                let next = window.start + self.per;
+                tokio::time::delay_until(next).await;
+                window.start = next;
+                window.used = 0;
+            }
+            window.used += 1;
+        }
+
+        self.inner.send(req).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::middleware::test::SequenceTransport;
+
+    #[tokio::test]
+    async fn test_rate_limit_waits() {
+        let t = SequenceTransport::new(&[200]);
+        let limit = RateLimitLayer::new(2, Duration::from_millis(50)).layer(t.clone());
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            limit.send(Request::new(Bytes::new())).await.unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(*t.calls.lock().unwrap(), 3);
+    }
+}
@@ -0,0 +1,349 @@
+use std::io;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use http::{header, Method, Request, Response, StatusCode};
+
+use super::{clone_request, Layer};
+use crate::error::Error;
+use crate::transport::{Bytes, Transport};
+
+/// Retries idempotent requests that fail with a network error or a `429`, `502`, `503` or `504`
+/// response, waiting with exponential backoff between attempts. A `Retry-After` header on the
+/// response is used instead of the backoff when it's present.
+///
+/// Errors that would fail the same way again, such as invalid requests, rejected proxy
+/// credentials or untrusted certificates, aren't retried.
+#[derive(Debug, Clone)]
+pub struct RetryLayer {
+    max_retries: u32,
+    backoff: Duration,
+    max_retry_after: Duration,
+}
+
+impl RetryLayer {
+    /// Retries each request at most `max_retries` times, starting with a 100ms backoff.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            backoff: Duration::from_millis(100),
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+
+    /// Sets the backoff before the first retry. It doubles after every attempt.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets the longest `Retry-After` delay that will be waited for, 60 seconds by default.
+    /// Responses asking for a longer delay are returned without retrying.
+    pub fn max_retry_after(mut self, max: Duration) -> Self {
+        self.max_retry_after = max;
+        self
+    }
+}
+
+impl<S: Transport> Layer<S> for RetryLayer {
+    type Service = Retry<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Retry {
+            inner,
+            config: self.clone(),
+        }
+    }
+}
+
+/// A transport created by [`RetryLayer`].
+pub struct Retry<S> {
+    inner: S,
+    config: RetryLayer,
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+    )
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Returns whether an error was caused by the network rather than by the request or the client's
+/// configuration. TLS and proxy failures surface as I/O errors of other kinds, so they aren't
+/// retried.
+fn is_retryable_error(err: &Error) -> bool {
+    match err {
+        Error::Hyper(err) if err.is_incomplete_message() || err.is_canceled() => true,
+        Error::Hyper(err) => io_source(err).is_some_and(is_transient),
+        Error::Io(err) => is_transient(err),
+        Error::Transport(err) => io_source(&**err).is_some_and(is_transient),
+        _ => false,
+    }
+}
+
+/// Returns the first I/O error in an error's chain of sources.
+fn io_source<'a>(err: &'a (dyn std::error::Error + 'static)) -> Option<&'a io::Error> {
+    let mut next = Some(err);
+    while let Some(err) = next {
+        if let Some(err) = err.downcast_ref::<io::Error>() {
+            return Some(err);
+        }
+        next = err.source();
+    }
+    None
+}
+
+fn is_transient(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::Interrupted
+    )
+}
+
+/// Parses a `Retry-After` header, given either as a number of seconds or as an HTTP date.
+fn retry_after(res: &Response<Bytes>, now: DateTime<Utc>) -> Option<Duration> {
+    let value = res
+        .headers()
+        .get(header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[async_trait]
+impl<S: Transport> Transport for Retry<S> {
+    async fn send(&self, req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+        if !is_idempotent(req.method()) {
+            return self.inner.send(req).await;
+        }
+
+        let mut backoff = self.config.backoff;
+        let mut attempt = 0;
+        loop {
+            let res = self.inner.send(clone_request(&req)).await;
+            let delay = match &res {
+                Ok(res) if is_retryable(res.status()) => match retry_after(res, Utc::now()) {
+                    Some(d) if d > self.config.max_retry_after => None,
+                    Some(d) => Some(d),
+                    None => Some(backoff),
+                },
+                Ok(_) => None,
+                Err(err) if is_retryable_error(err) => Some(backoff),
+                Err(_) => None,
+            };
+            let delay = match delay {
+                Some(delay) if attempt < self.config.max_retries => delay,
+                _ => return res,
+            };
+
+            attempt += 1;
+            #[cfg(feature = "tracing")]
+            match &res {
+                Ok(res) => tracing::info!(
+                    attempt,
+                    status = res.status().as_u16(),
+                    delay_ms = delay.as_millis() as u64,
+                    "retrying request"
+                ),
+                Err(err) => tracing::info!(
+                    attempt,
+                    error = %err,
+                    delay_ms = delay.as_millis() as u64,
+                    "retrying request"
+                ),
+            }
+            tokio::time::delay_for(delay).await;
+            backoff *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::Executor;
+    use crate::middleware::test::SequenceTransport;
+    use crate::transport::test::MockTransport;
+    use crate::Coder;
+
+    fn client(t: std::sync::Arc<SequenceTransport>, retries: u32) -> Coder {
+        Coder::with_transport("https://coder.example.com".into(), "key", t)
+            .unwrap()
+            .layer(RetryLayer::new(retries).backoff(Duration::from_millis(1)))
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds() {
+        let t = SequenceTransport::new(&[503, 502, 200]);
+        let res = client(t.clone(), 3)
+            .orgs()
+            .namespaces()
+            .execute()
+            .await
+            .expect("send request");
+        assert_eq!(res.status_code, 200);
+        assert_eq!(*t.calls.lock().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up() {
+        let t = SequenceTransport::new(&[503]);
+        let res = client(t.clone(), 2)
+            .orgs()
+            .namespaces()
+            .execute()
+            .await
+            .expect("send request");
+        assert_eq!(res.status_code, 503);
+        assert_eq!(*t.calls.lock().unwrap(), 3);
+    }
+
+    /// A transport that fails every request with the error returned by `err`.
+    struct FailingTransport<F> {
+        err: F,
+        calls: std::sync::Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl<F: Fn() -> Error + Send + Sync> Transport for FailingTransport<F> {
+        async fn send(&self, _req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+            *self.calls.lock().unwrap() += 1;
+            Err((self.err)())
+        }
+    }
+
+    async fn calls_until_error<F: Fn() -> Error + Send + Sync + 'static>(err: F) -> usize {
+        let t = std::sync::Arc::new(FailingTransport {
+            err,
+            calls: std::sync::Mutex::new(0),
+        });
+        let retry = RetryLayer::new(2)
+            .backoff(Duration::from_millis(1))
+            .layer(t.clone());
+        retry.send(Request::new(Bytes::new())).await.unwrap_err();
+        let calls = *t.calls.lock().unwrap();
+        calls
+    }
+
+    #[tokio::test]
+    async fn test_retry_errors() {
+        let io = |kind| move || Error::Io(io::Error::new(kind, "failed"));
+        assert_eq!(
+            calls_until_error(io(io::ErrorKind::ConnectionReset)).await,
+            3
+        );
+        assert_eq!(calls_until_error(io(io::ErrorKind::InvalidData)).await, 1);
+        assert_eq!(
+            calls_until_error(|| Error::Builder(crate::BuilderError::Body("bad".into()))).await,
+            1
+        );
+        assert_eq!(
+            calls_until_error(|| Error::Transport(
+                io::Error::new(io::ErrorKind::TimedOut, "timed out").into()
+            ))
+            .await,
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_connect_errors() {
+        // Connections to a port that was just released are refused.
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let t = crate::transport::HyperTransport::builder()
+            .no_proxy()
+            .build();
+        let uri = format!("http://127.0.0.1:{}/api/users/me", port);
+        let err = t
+            .send(Request::get(uri).body(Bytes::new()).unwrap())
+            .await
+            .unwrap_err();
+        assert!(is_retryable_error(&err), "{:?}", err);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let res = |value: &str| {
+            Response::builder()
+                .status(503)
+                .header("Retry-After", value)
+                .body(Bytes::new())
+                .unwrap()
+        };
+        let now = "2020-05-29T18:10:33Z".parse().unwrap();
+        assert_eq!(
+            retry_after(&res("120"), now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            retry_after(&res("Fri, 29 May 2020 18:11:03 GMT"), now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            retry_after(&res("Fri, 29 May 2020 18:00:00 GMT"), now),
+            Some(Duration::from_secs(0))
+        );
+        assert_eq!(retry_after(&res("soon"), now), None);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_too_long() {
+        let t = MockTransport::new(429, "{}");
+        let retry = RetryLayer::new(3)
+            .max_retry_after(Duration::from_secs(1))
+            .layer(
+                crate::middleware::from_fn(|req, next| async move {
+                    let mut res = next.run(req).await?;
+                    res.headers_mut()
+                        .insert(header::RETRY_AFTER, "3600".parse().unwrap());
+                    Ok(res)
+                })
+                .layer(t.clone()),
+            );
+        let res = retry.send(Request::new(Bytes::new())).await.unwrap();
+        assert_eq!(res.status(), 429);
+        assert_eq!(t.requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_skips_non_idempotent() {
+        let t = SequenceTransport::new(&[503, 200]);
+        let mut req = Request::new(Bytes::new());
+        *req.method_mut() = Method::POST;
+        let retry = RetryLayer::new(3).layer(t.clone());
+        let res = retry.send(req).await.unwrap();
+        assert_eq!(res.status(), 503);
+        assert_eq!(*t.calls.lock().unwrap(), 1);
+    }
+}
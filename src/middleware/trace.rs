@@ -0,0 +1,175 @@
+use async_trait::async_trait;
+use http::{Request, Response};
+use tracing::field::Empty;
+use tracing::Instrument;
+
+use super::Layer;
+use crate::client::Route;
+use crate::error::Error;
+use crate::transport::{Bytes, Transport};
+
+/// Opens a `coder.send` span for every request that passes through, with the method, route
+/// template, status and latency. Like the `coder.request` span opened by `execute`, the URL,
+/// headers and bodies aren't recorded.
+///
+/// Every executed request is already traced when the `tracing` feature is enabled. This layer
+/// traces part of the middleware chain instead, for example each attempt made by a
+/// [`RetryLayer`](super::RetryLayer) added after it.
+#[derive(Debug, Clone, Default)]
+pub struct TraceLayer {
+    _priv: (),
+}
+
+impl TraceLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S: Transport> Layer<S> for TraceLayer {
+    type Service = Trace<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Trace { inner }
+    }
+}
+
+/// A transport created by [`TraceLayer`].
+pub struct Trace<S> {
+    inner: S,
+}
+
+#[async_trait]
+impl<S: Transport> Transport for Trace<S> {
+    async fn send(&self, req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+        let route = req.extensions().get::<Route>().cloned().unwrap_or_default();
+        let span = tracing::info_span!(
+            "coder.send",
+            method = %req.method(),
+            route = %route,
+            status = Empty,
+            latency_ms = Empty,
+        );
+
+        let start = std::time::Instant::now();
+        let res = self.inner.send(req).instrument(span.clone()).await;
+        span.record("latency_ms", start.elapsed().as_millis() as u64);
+        match &res {
+            Ok(res) => {
+                span.record("status", res.status().as_u16());
+            }
+            Err(err) => tracing::debug!(parent: &span, error = %err, "request failed"),
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use super::*;
+    use crate::client::Executor;
+    use crate::middleware::test::SequenceTransport;
+    use crate::middleware::RetryLayer;
+    use crate::Coder;
+
+    /// A span recorded by [`SpanRecorder`], with its fields formatted as strings.
+    #[derive(Debug, Clone)]
+    pub(crate) struct RecordedSpan {
+        pub(crate) name: &'static str,
+        pub(crate) fields: HashMap<&'static str, String>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut HashMap<&'static str, String>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name(), value.to_string());
+        }
+    }
+
+    /// A subscriber that records every span and the fields recorded on it.
+    #[derive(Clone, Default)]
+    pub(crate) struct SpanRecorder {
+        next_id: Arc<AtomicU64>,
+        spans: Arc<Mutex<Vec<RecordedSpan>>>,
+    }
+
+    impl SpanRecorder {
+        pub(crate) fn spans(&self, name: &str) -> Vec<RecordedSpan> {
+            let spans = self.spans.lock().unwrap();
+            spans.iter().filter(|s| s.name == name).cloned().collect()
+        }
+    }
+
+    impl Subscriber for SpanRecorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let mut span = RecordedSpan {
+                name: attrs.metadata().name(),
+                fields: HashMap::new(),
+            };
+            attrs.record(&mut FieldVisitor(&mut span.fields));
+            self.spans.lock().unwrap().push(span);
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        fn record(&self, id: &Id, values: &Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            let span = &mut spans[id.into_u64() as usize - 1];
+            values.record(&mut FieldVisitor(&mut span.fields));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    /// Runs a future on a new runtime with `recorder` as the default subscriber.
+    pub(crate) fn record<F: std::future::Future>(recorder: &SpanRecorder, f: F) -> F::Output {
+        let mut rt = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .unwrap();
+        tracing::subscriber::with_default(recorder.clone(), || rt.block_on(f))
+    }
+
+    #[test]
+    fn test_trace_layer_spans_attempts() {
+        let t = SequenceTransport::new(&[503, 200]);
+        let c = Coder::with_transport("https://coder.example.com".into(), "key", t)
+            .unwrap()
+            .layer(TraceLayer::new())
+            .layer(RetryLayer::new(1).backoff(Duration::from_millis(1)));
+
+        let recorder = SpanRecorder::default();
+        record(&recorder, c.orgs().namespaces().execute()).expect("send request");
+
+        let spans = recorder.spans("coder.send");
+        let statuses: Vec<_> = spans.iter().map(|s| s.fields["status"].as_str()).collect();
+        assert_eq!(statuses, vec!["503", "200"]);
+        for span in &spans {
+            assert_eq!(span.fields["method"], "GET");
+            assert_eq!(span.fields["route"], "/orgs/namespaces");
+            assert!(span.fields.contains_key("latency_ms"));
+        }
+    }
+}
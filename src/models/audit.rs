@@ -0,0 +1,146 @@
+use std::io::Write;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{OrgId, UserId};
+
+/// A single entry in the audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub id: String,
+    pub time: DateTime<Utc>,
+    pub organization_id: OrgId,
+    pub actor_id: UserId,
+    pub actor_username: String,
+    pub action: AuditAction,
+    pub resource_type: AuditResourceType,
+    pub resource_id: String,
+    pub resource_name: String,
+    /// Action specific details, such as the roles before and after a role change.
+    pub details: Option<serde_json::Value>,
+}
+
+impl AuditEvent {
+    /// Writes events as [JSON Lines](https://jsonlines.org), one event per line. Write failures
+    /// are returned as [`Error::Io`](crate::Error::Io).
+    pub fn write_json_lines<'a, W, I>(mut w: W, events: I) -> Result<(), crate::Error>
+    where
+        W: Write,
+        I: IntoIterator<Item = &'a AuditEvent>,
+    {
+        for event in events {
+            serde_json::to_writer(&mut w, event).map_err(|err| match err.classify() {
+                serde_json::error::Category::Io => crate::Error::Io(err.into()),
+                _ => crate::Error::Serde(err),
+            })?;
+            w.write_all(b"\n")?;
+        }
+        w.flush()?;
+        Ok(())
+    }
+}
+
+string_enum!(
+    /// The action recorded by an audit event.
+    pub enum AuditAction {
+        Create = "create",
+        Update = "update",
+        Delete = "delete",
+        Rebuild = "rebuild",
+        Start = "start",
+        Stop = "stop",
+        RoleChange = "role_change",
+        Login = "login",
+        Logout = "logout",
+    }
+);
+
+string_enum!(
+    /// The type of resource an audit event applies to.
+    pub enum AuditResourceType {
+        Environment = "environment",
+        User = "user",
+        Organization = "organization",
+        OrgMember = "org_member",
+        Image = "image",
+        Registry = "registry",
+        Service = "service",
+        DevUrl = "dev_url",
+    }
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn event(id: &str, action: AuditAction) -> AuditEvent {
+        AuditEvent {
+            id: id.into(),
+            time: Utc::now(),
+            organization_id: "default".into(),
+            actor_id: "5e876cf4-10abe9b2e54eb609c5ec1870".into(),
+            actor_username: "colin".into(),
+            action,
+            resource_type: AuditResourceType::Environment,
+            resource_id: "5ed15061-d7d3db1d91600a4fed28f6ed".into(),
+            resource_name: "dev".into(),
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_write_json_lines() {
+        let events = vec![
+            event("1", AuditAction::Create),
+            event("2", AuditAction::Rebuild),
+        ];
+
+        let mut buf = Vec::new();
+        AuditEvent::write_json_lines(&mut buf, &events).unwrap();
+
+        let out = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), events.len());
+        for (line, event) in lines.iter().zip(&events) {
+            let parsed: AuditEvent = serde_json::from_str(line).unwrap();
+            assert_eq!(&parsed, event);
+        }
+    }
+
+    #[test]
+    fn test_write_json_lines_io_error() {
+        struct Full;
+
+        impl Write for Full {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::WriteZero,
+                    "disk full",
+                ))
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let err = AuditEvent::write_json_lines(Full, &[event("1", AuditAction::Create)]);
+        match err {
+            Err(crate::Error::Io(err)) => assert_eq!(err.kind(), std::io::ErrorKind::WriteZero),
+            res => panic!("unexpected result {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_enum_display_matches_serde() {
+        assert_eq!(
+            serde_json::to_string(&AuditAction::RoleChange).unwrap(),
+            format!("\"{}\"", AuditAction::RoleChange)
+        );
+        assert_eq!(
+            serde_json::to_string(&AuditResourceType::OrgMember).unwrap(),
+            format!("\"{}\"", AuditResourceType::OrgMember)
+        );
+    }
+}
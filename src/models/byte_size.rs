@@ -0,0 +1,296 @@
+use std::fmt;
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde::{Deserializer, Serializer};
+
+use super::ParseUnitError;
+
+const KIB: u64 = 1 << 10;
+const MIB: u64 = 1 << 20;
+const GIB: u64 = 1 << 30;
+const TIB: u64 = 1 << 40;
+
+/// ByteSize is an amount of memory or storage. It Serializes into and Deserializes from an
+/// integer number of bytes. Fields sent in other units use an adapter such as [`gib`].
+///
+/// ByteSize can be parsed from and formatted as human readable strings such as `"16GiB"`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(u64);
+
+impl ByteSize {
+    pub const fn bytes(n: u64) -> Self {
+        Self(n)
+    }
+
+    pub const fn kib(n: u64) -> Self {
+        Self(n * KIB)
+    }
+
+    pub const fn mib(n: u64) -> Self {
+        Self(n * MIB)
+    }
+
+    pub const fn gib(n: u64) -> Self {
+        Self(n * GIB)
+    }
+
+    pub const fn as_bytes(&self) -> u64 {
+        self.0
+    }
+
+    pub fn as_gib(&self) -> f64 {
+        self.0 as f64 / GIB as f64
+    }
+
+    /// Returns the percentage of `total` this size makes up. Returns 0 if `total` is empty.
+    pub fn percent_of(&self, total: ByteSize) -> f64 {
+        if total.0 == 0 {
+            return 0.0;
+        }
+        self.0 as f64 / total.0 as f64 * 100.0
+    }
+}
+
+impl Add for ByteSize {
+    type Output = ByteSize;
+
+    fn add(self, rhs: ByteSize) -> ByteSize {
+        ByteSize(self.0 + rhs.0)
+    }
+}
+
+impl Sub for ByteSize {
+    type Output = ByteSize;
+
+    fn sub(self, rhs: ByteSize) -> ByteSize {
+        ByteSize(self.0.saturating_sub(rhs.0))
+    }
+}
+
+const UNITS: &[(&str, u64)] = &[
+    ("TiB", TIB),
+    ("GiB", GIB),
+    ("MiB", MIB),
+    ("KiB", KIB),
+    ("Ti", TIB),
+    ("Gi", GIB),
+    ("Mi", MIB),
+    ("Ki", KIB),
+    ("TB", 1_000_000_000_000),
+    ("GB", 1_000_000_000),
+    ("MB", 1_000_000),
+    ("KB", 1_000),
+    ("kB", 1_000),
+    ("B", 1),
+];
+
+/// Formats using the largest binary unit, e.g. `16GiB` or `1.5MiB`.
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (unit, size) = UNITS[..4]
+            .iter()
+            .find(|(_, size)| self.0 >= *size)
+            .copied()
+            .unwrap_or(("B", 1));
+
+        if self.0 % size == 0 {
+            write!(f, "{}{}", self.0 / size, unit)
+        } else {
+            let v = format!("{:.2}", self.0 as f64 / size as f64);
+            write!(
+                f,
+                "{}{}",
+                v.trim_end_matches('0').trim_end_matches('.'),
+                unit
+            )
+        }
+    }
+}
+
+/// Parses sizes such as `16GiB`, `16Gi`, `512MB` or `1024`. Binary units (`KiB`, `Ki`, ...) are
+/// powers of 1024, decimal units (`KB`, `MB`, ...) are powers of 1000 and no unit means bytes.
+impl FromStr for ByteSize {
+    type Err = ParseUnitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len());
+        let (num, unit) = s.split_at(split);
+        let unit = unit.trim();
+
+        let num: f64 = num
+            .parse()
+            .map_err(|_| ParseUnitError::new("byte size", s))?;
+        let size = if unit.is_empty() {
+            1
+        } else {
+            UNITS
+                .iter()
+                .find(|(u, _)| *u == unit)
+                .map(|(_, size)| *size)
+                .ok_or_else(|| ParseUnitError::new("byte size", s))?
+        };
+
+        Ok(ByteSize((num * size as f64).round() as u64))
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D>(deserializer: D) -> Result<ByteSize, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let u = u64::deserialize(deserializer)?;
+        Ok(ByteSize(u))
+    }
+}
+
+impl Serialize for ByteSize {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+/// Implemented for fields that can be used with the byte size serde adapters, so the same adapter
+/// works for both `ByteSize` and `Option<ByteSize>`.
+pub trait ByteSizeField: Sized {
+    fn serialize_in<S: Serializer>(&self, unit: u64, serializer: S) -> Result<S::Ok, S::Error>;
+    fn deserialize_in<'de, D: Deserializer<'de>>(
+        unit: u64,
+        deserializer: D,
+    ) -> Result<Self, D::Error>;
+}
+
+impl ByteSizeField for ByteSize {
+    fn serialize_in<S: Serializer>(&self, unit: u64, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0 % unit != 0 {
+            return Err(serde::ser::Error::custom(format!(
+                "{} is not a whole number of {} byte units",
+                self, unit
+            )));
+        }
+        serializer.serialize_u64(self.0 / unit)
+    }
+
+    fn deserialize_in<'de, D: Deserializer<'de>>(
+        unit: u64,
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let u = u64::deserialize(deserializer)?;
+        u.checked_mul(unit)
+            .map(ByteSize)
+            .ok_or_else(|| serde::de::Error::custom("byte size overflows u64"))
+    }
+}
+
+impl ByteSizeField for Option<ByteSize> {
+    fn serialize_in<S: Serializer>(&self, unit: u64, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Some(size) => size.serialize_in(unit, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    fn deserialize_in<'de, D: Deserializer<'de>>(
+        unit: u64,
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let u = Option::<u64>::deserialize(deserializer)?;
+        u.map(|u| {
+            u.checked_mul(unit)
+                .map(ByteSize)
+                .ok_or_else(|| serde::de::Error::custom("byte size overflows u64"))
+        })
+        .transpose()
+    }
+}
+
+/// Serde adapter for byte sizes sent as an integer number of gibibytes, such as
+/// `Environment.memory_gb`. Use with `#[serde(with = "coder::models::gib")]`.
+pub mod gib {
+    use super::{ByteSizeField, GIB};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<T: ByteSizeField, S: Serializer>(
+        v: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        v.serialize_in(GIB, serializer)
+    }
+
+    pub fn deserialize<'de, T: ByteSizeField, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        T::deserialize_in(GIB, deserializer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ByteSize;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sizes {
+        total: ByteSize,
+        #[serde(with = "super::gib")]
+        memory_gb: ByteSize,
+        #[serde(with = "super::gib")]
+        disk_gb: Option<ByteSize>,
+    }
+
+    #[test]
+    fn test_serde_adapters() {
+        let json = r#"{"total":1073741824,"memory_gb":4,"disk_gb":null}"#;
+        let sizes: Sizes = serde_json::from_str(json).unwrap();
+        assert_eq!(sizes.total, ByteSize::gib(1));
+        assert_eq!(sizes.memory_gb, ByteSize::gib(4));
+        assert_eq!(sizes.disk_gb, None);
+        assert_eq!(serde_json::to_string(&sizes).unwrap(), json);
+    }
+
+    #[test]
+    fn test_serialize_partial_gib_fails() {
+        let sizes = Sizes {
+            total: ByteSize::gib(1),
+            memory_gb: ByteSize::mib(1536),
+            disk_gb: None,
+        };
+        assert!(serde_json::to_string(&sizes).is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("16GiB".parse::<ByteSize>().unwrap(), ByteSize::gib(16));
+        assert_eq!("16Gi".parse::<ByteSize>().unwrap(), ByteSize::gib(16));
+        assert_eq!("1.5 MiB".parse::<ByteSize>().unwrap(), ByteSize::kib(1536));
+        assert_eq!(
+            "2GB".parse::<ByteSize>().unwrap(),
+            ByteSize::bytes(2_000_000_000)
+        );
+        assert_eq!("1024".parse::<ByteSize>().unwrap(), ByteSize::kib(1));
+        assert!("16 gigs".parse::<ByteSize>().is_err());
+        assert!("GiB".parse::<ByteSize>().is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ByteSize::gib(16).to_string(), "16GiB");
+        assert_eq!(ByteSize::mib(1536).to_string(), "1.5GiB");
+        assert_eq!(ByteSize::bytes(512).to_string(), "512B");
+        assert_eq!(ByteSize::bytes(0).to_string(), "0B");
+    }
+
+    #[test]
+    fn test_percent_of() {
+        assert_eq!(ByteSize::gib(1).percent_of(ByteSize::gib(4)), 25.0);
+        assert_eq!(ByteSize::gib(1).percent_of(ByteSize::bytes(0)), 0.0);
+    }
+}
@@ -0,0 +1,30 @@
+//! Serde helpers for tolerating differences between manager releases.
+//!
+//! Older managers omit some fields entirely (such as `Environment.service_ids` or
+//! `ImageTag.os_release`), and Go's `encoding/json` sends `null` for unset slices and timestamps.
+//! By default, fields like these are deserialized with a default value or as `None`. Enabling the
+//! `strict` feature makes them required again, which is useful for contract tests against a
+//! specific manager release.
+
+use serde::{Deserialize, Deserializer};
+
+/// Deserializes `null` as the default value of `T`. Combined with `#[serde(default)]` this also
+/// covers missing fields. Not used by the `strict` feature.
+#[cfg(not(feature = "strict"))]
+pub(crate) fn or_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Requires an optional field to be present and non-null. Only used by the `strict` feature.
+#[cfg(feature = "strict")]
+pub(crate) fn required<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
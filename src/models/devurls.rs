@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DevUrlId(pub String);
+// Only checked as a path segment: nothing in the API docs or the recorded responses confirms that
+// dev URL ids are object ids.
+id_string!(DevUrlId => segment);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DevUrl {
+    pub id: DevUrlId,
+    pub url: String,
+    pub port: u16,
+    pub access: DevUrlAccess,
+    pub name: String,
+    pub scheme: String,
+}
+
+string_enum!(
+    /// Who is allowed to access a dev URL.
+    pub enum DevUrlAccess {
+        /// Only the owner of the environment.
+        Private = "PRIVATE",
+        /// Members of the environment's organization.
+        Org = "ORG",
+        /// Any authenticated user.
+        Authed = "AUTHED",
+        /// Anyone, without authentication.
+        Public = "PUBLIC",
+    }
+);
+
+/// Request body for creating or updating a dev URL.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DevUrlRequest {
+    pub port: u16,
+    pub name: String,
+    pub access: DevUrlAccess,
+}
@@ -1,11 +1,124 @@
+use std::fmt;
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 use serde::{Deserializer, Serializer};
 
 /// Duration is a wrapper around chrono::Duration that Serializes into and Deserializes from
 /// millisecond precision integers.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Duration can be parsed from and formatted as human readable strings such as `"8h"` or
+/// `"1h30m"`.
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Duration(chrono::Duration);
 
+impl From<chrono::Duration> for Duration {
+    fn from(d: chrono::Duration) -> Self {
+        Duration(d)
+    }
+}
+
+const UNITS: &[(&str, i64)] = &[
+    ("d", 24 * 60 * 60 * 1000),
+    ("h", 60 * 60 * 1000),
+    ("m", 60 * 1000),
+    ("s", 1000),
+    ("ms", 1),
+];
+
+/// Formats as a sequence of units, e.g. `1d2h`, `1h30m` or `250ms`.
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ms = self.0.num_milliseconds();
+        if ms == 0 {
+            return f.write_str("0s");
+        }
+        if ms < 0 {
+            f.write_str("-")?;
+            ms = -ms;
+        }
+
+        for (unit, size) in UNITS {
+            if ms >= *size {
+                write!(f, "{}{}", ms / size, unit)?;
+                ms %= size;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses a sequence of integer durations with units, such as `8h`, `1h30m` or `500ms`. Valid
+/// units are `d`, `h`, `m`, `s` and `ms`, and each can be used at most once.
+impl FromStr for Duration {
+    type Err = ParseUnitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseUnitError::new("duration", s);
+        let (neg, mut rest) = match s.trim() {
+            t if t.starts_with('-') => (true, &t[1..]),
+            t => (false, t),
+        };
+        if rest.is_empty() {
+            return Err(err());
+        }
+
+        let mut ms: i64 = 0;
+        let mut seen = [false; UNITS.len()];
+        while !rest.is_empty() {
+            let split = rest.find(|c: char| !c.is_ascii_digit()).ok_or_else(err)?;
+            let (num, tail) = rest.split_at(split);
+            let num: i64 = num.parse().map_err(|_| err())?;
+
+            let unit_len = tail
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(tail.len());
+            let (unit, tail) = tail.split_at(unit_len);
+            let i = UNITS.iter().position(|(u, _)| *u == unit).ok_or_else(err)?;
+            if std::mem::replace(&mut seen[i], true) {
+                return Err(err());
+            }
+            let size = UNITS[i].1;
+
+            ms = num
+                .checked_mul(size)
+                .and_then(|n| ms.checked_add(n))
+                .ok_or_else(err)?;
+            rest = tail;
+        }
+
+        Ok(Duration(chrono::Duration::milliseconds(if neg {
+            -ms
+        } else {
+            ms
+        })))
+    }
+}
+
+/// An error returned when parsing a human readable duration or byte size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseUnitError {
+    kind: &'static str,
+    input: String,
+}
+
+impl ParseUnitError {
+    pub(crate) fn new(kind: &'static str, input: &str) -> Self {
+        Self {
+            kind,
+            input: input.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseUnitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}: {:?}", self.kind, self.input)
+    }
+}
+
+impl std::error::Error for ParseUnitError {}
+
 // Allow Duration to be used as a chrono::Duration.
 impl std::ops::Deref for Duration {
     type Target = chrono::Duration;
@@ -52,4 +165,27 @@ mod test {
         let d: Duration = serde_json::from_str(&ms.to_string()).unwrap();
         assert_eq!(d.num_milliseconds(), ms);
     }
+
+    #[test]
+    fn test_parse_duration() {
+        let parse = |s: &str| s.parse::<Duration>().map(|d| d.num_milliseconds());
+        assert_eq!(parse("8h"), Ok(8 * 60 * 60 * 1000));
+        assert_eq!(parse("1h30m"), Ok(90 * 60 * 1000));
+        assert_eq!(parse("250ms"), Ok(250));
+        assert_eq!(parse("-1s"), Ok(-1000));
+        assert!(parse("").is_err());
+        assert!(parse("8").is_err());
+        assert!(parse("8 hours").is_err());
+        assert!(parse("1h1h").is_err());
+        assert!(parse("1h30m1h").is_err());
+    }
+
+    #[test]
+    fn test_display_duration() {
+        let d = |ms| Duration(chrono::Duration::milliseconds(ms)).to_string();
+        assert_eq!(d(8 * 60 * 60 * 1000), "8h");
+        assert_eq!(d(26 * 60 * 60 * 1000 + 5000), "1d2h5s");
+        assert_eq!(d(1500), "1s500ms");
+        assert_eq!(d(0), "0s");
+    }
 }
@@ -3,32 +3,89 @@ use super::*;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EnvId(pub String);
+id_string!(EnvId => object_id);
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Environment {
-    pub id: String,
+    pub id: EnvId,
     pub name: String,
     pub username: String,
-    pub image_id: String,
+    pub image_id: ImageId,
     pub image_tag: String,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub image_digest: String,
-    pub organization_id: String,
-    pub user_id: String,
+    pub organization_id: OrgId,
+    pub user_id: UserId,
     pub last_built_at: DateTime<Utc>,
     pub cpu_cores: f64,
-    pub memory_gb: i64,
-    pub disk_gb: i64,
+    #[serde(with = "gib")]
+    pub memory_gb: ByteSize,
+    #[serde(with = "gib")]
+    pub disk_gb: ByteSize,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub gpus: i64,
     pub latest_stat: EnvironmentStat,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub updating: bool,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub rebuild_messages: Vec<RebuildMessage>,
-    pub last_opened_at: DateTime<Utc>,
-    pub last_connection_at: DateTime<Utc>,
+    #[cfg_attr(feature = "strict", serde(deserialize_with = "compat::required"))]
+    pub last_opened_at: Option<DateTime<Utc>>,
+    #[cfg_attr(feature = "strict", serde(deserialize_with = "compat::required"))]
+    pub last_connection_at: Option<DateTime<Utc>>,
     pub auto_off_threshold: Duration,
-    pub service_ids: Vec<String>,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
+    pub service_ids: Vec<ServiceId>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Environment {
+    /// Returns when the environment will be automatically shut off if nobody connects to it, or
+    /// `None` if auto-off is disabled or the environment has never been connected to.
+    pub fn auto_off_at(&self) -> Option<DateTime<Utc>> {
+        if self.auto_off_threshold.is_zero() {
+            return None;
+        }
+        self.last_connection_at
+            .map(|t| t + *self.auto_off_threshold)
+    }
+}
+
+patch!(
+    /// A partial update to an `Environment`.
+    Environment => EnvironmentPatch {
+        name: String,
+        image_id: ImageId,
+        image_tag: String,
+        cpu_cores: f64,
+        #[serde(with = "gib")]
+        memory_gb: ByteSize,
+        #[serde(with = "gib")]
+        disk_gb: ByteSize,
+        gpus: i64,
+        auto_off_threshold: Duration,
+        service_ids: Vec<ServiceId>,
+    }
+);
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RebuildMessage {
     pub text: String,
@@ -38,22 +95,120 @@ pub struct RebuildMessage {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EnvironmentStat {
     pub time: DateTime<Utc>,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub last_online: String,
     pub container_status: ContainerStatus,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub stat_error: String,
     pub cpu_usage: f32,
-    pub memory_total: i64,
+    pub memory_total: ByteSize,
     pub memory_usage: f32,
-    pub disk_total: i64,
-    pub disk_used: i64,
+    pub disk_total: ByteSize,
+    pub disk_used: ByteSize,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub service_stat: Vec<ServiceStat>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum ContainerStatus {
-    CREATING,
-    OFF,
-    ON,
-    FAILED,
-    UNKNOWN,
+impl EnvironmentStat {
+    /// Returns the percentage of the environment's disk that is in use.
+    pub fn disk_used_percent(&self) -> f64 {
+        self.disk_used.percent_of(self.disk_total)
+    }
+}
+
+string_enum!(
+    pub enum ContainerStatus {
+        CREATING = "CREATING",
+        OFF = "OFF",
+        ON = "ON",
+        FAILED = "FAILED",
+        /// The server was unable to determine the status of the container.
+        UNKNOWN = "UNKNOWN",
+    }
+);
+
+#[cfg(test)]
+mod test {
+    use super::Environment;
+
+    // An environment as returned by an older manager, which doesn't send `service_ids` and sends
+    // `null` for slices and timestamps that were never set.
+    const OLD_ENV: &str = r#"{
+        "id": "5ed15061-d7d3db1d91600a4fed28f6ed",
+        "name": "dev",
+        "username": "colin",
+        "image_id": "5ea8a569-596e6afd9301c23f8dabd87c",
+        "image_tag": "latest",
+        "image_digest": "sha256:5d1b4f1a",
+        "organization_id": "default",
+        "user_id": "5e876cf4-10abe9b2e54eb609c5ec1870",
+        "last_built_at": "2020-05-29T18:10:33.532351Z",
+        "cpu_cores": 2,
+        "memory_gb": 4,
+        "disk_gb": 10,
+        "gpus": 0,
+        "latest_stat": {
+            "time": "2020-05-29T18:10:33.532351Z",
+            "last_online": "2020-05-29T18:10:33.532351Z",
+            "container_status": "ON",
+            "stat_error": "",
+            "cpu_usage": 0.5,
+            "memory_total": 4294967296,
+            "memory_usage": 0.25,
+            "disk_total": 10737418240,
+            "disk_used": 1073741824,
+            "service_stat": null
+        },
+        "updating": false,
+        "rebuild_messages": null,
+        "last_opened_at": null,
+        "last_connection_at": "2020-05-29T18:10:33.532351Z",
+        "auto_off_threshold": 86400000,
+        "created_at": "2020-05-29T18:10:33.532351Z",
+        "updated_at": "2020-05-29T18:10:33.532351Z"
+    }"#;
+
+    #[cfg(not(feature = "strict"))]
+    #[test]
+    fn test_deserialize_old_manager() {
+        use super::ByteSize;
+
+        let env: Environment = serde_json::from_str(OLD_ENV).unwrap();
+        assert!(env.service_ids.is_empty());
+        assert!(env.rebuild_messages.is_empty());
+        assert!(env.latest_stat.service_stat.is_empty());
+        assert!(env.last_opened_at.is_none());
+        assert!(env.last_connection_at.is_some());
+        assert_eq!(env.memory_gb, ByteSize::gib(4));
+        assert_eq!(env.latest_stat.disk_used_percent(), 10.0);
+        assert_eq!(
+            env.auto_off_at(),
+            Some("2020-05-30T18:10:33.532351Z".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_container_status() {
+        use super::ContainerStatus;
+
+        let status: ContainerStatus = serde_json::from_str("\"UNKNOWN\"").unwrap();
+        assert_eq!(status, ContainerStatus::UNKNOWN);
+        let status: ContainerStatus = serde_json::from_str("\"PAUSED\"").unwrap();
+        assert_eq!(status, ContainerStatus::Unknown("PAUSED".into()));
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    fn test_deserialize_old_manager_strict() {
+        assert!(serde_json::from_str::<Environment>(OLD_ENV).is_err());
+    }
 }
@@ -3,36 +3,69 @@ use super::*;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ImageId(pub String);
-id_string!(ImageId);
+id_string!(ImageId => object_id);
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Image {
     pub id: ImageId,
-    pub organization_id: String,
+    pub organization_id: OrgId,
     pub repository: String,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub description: String,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub url: String,
     pub default_cpu_cores: i64,
-    pub default_memory_gb: i64,
-    pub default_disk_gb: i64,
+    #[serde(with = "gib")]
+    pub default_memory_gb: ByteSize,
+    #[serde(with = "gib")]
+    pub default_disk_gb: ByteSize,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub deprecated: bool,
     pub registry: Registry,
-    pub default_tag: ImageTag,
+    #[cfg_attr(feature = "strict", serde(deserialize_with = "compat::required"))]
+    pub default_tag: Option<ImageTag>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 
     /// All environments using this image. Must opt in to receive.
     pub environments: Option<Vec<Environment>>,
     /// IDs of all users using this image. Must opt in to receive.
-    pub user_ids: Option<Vec<String>>,
+    pub user_ids: Option<Vec<UserId>>,
 }
 
+patch!(
+    /// A partial update to an `Image`.
+    Image => ImagePatch {
+        description: String,
+        url: String,
+        default_cpu_cores: i64,
+        #[serde(with = "gib")]
+        default_memory_gb: ByteSize,
+        #[serde(with = "gib")]
+        default_disk_gb: ByteSize,
+        deprecated: bool,
+    }
+);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RegistryId(pub String);
+id_string!(RegistryId => object_id);
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Registry {
-    pub id: String,
-    pub organization_id: String,
+    pub id: RegistryId,
+    pub organization_id: OrgId,
     pub friendly_name: String,
     pub registry: String,
     pub created_at: DateTime<Utc>,
@@ -41,12 +74,22 @@ pub struct Registry {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ImageTag {
-    pub image_id: String,
+    pub image_id: ImageId,
     pub tag: String,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub latest_hash: String,
-    pub hash_last_updated_at: DateTime<Utc>,
+    #[cfg_attr(feature = "strict", serde(deserialize_with = "compat::required"))]
+    pub hash_last_updated_at: Option<DateTime<Utc>>,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub environments: Vec<Environment>,
-    pub os_release: OsRelease,
+    #[cfg_attr(feature = "strict", serde(deserialize_with = "compat::required"))]
+    pub os_release: Option<OsRelease>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -1,13 +1,22 @@
+mod audit;
+mod byte_size;
+mod compat;
+mod devurls;
 mod duration;
 mod envs;
 mod images;
 mod orgs;
+mod secrets;
 mod services;
 mod users;
 
+pub use audit::*;
+pub use byte_size::*;
+pub use devurls::*;
 pub use duration::*;
 pub use envs::*;
 pub use images::*;
 pub use orgs::*;
+pub use secrets::*;
 pub use services::*;
 pub use users::*;
@@ -1,21 +1,66 @@
+use super::Duration;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OrgId(pub String);
+// Unlike other ids, org ids aren't always object ids: the default organization's id is
+// `default`.
+id_string!(OrgId => segment);
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Organization {
-    pub id: String,
+    pub id: OrgId,
     pub name: String,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "super::compat::or_default")
+    )]
     pub description: String,
     pub default: bool,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "super::compat::or_default")
+    )]
     pub members: Vec<OrgMember>,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "super::compat::or_default")
+    )]
     pub environment_count: i64,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "super::compat::or_default")
+    )]
     pub resource_namespace: String,
-    pub auto_off_threshold: i64,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "super::compat::or_default")
+    )]
+    /// The default auto-off threshold for environments in the organization. Sent in milliseconds,
+    /// like [`Environment::auto_off_threshold`](super::Environment::auto_off_threshold).
+    pub auto_off_threshold: Duration,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "super::compat::or_default")
+    )]
     pub cpu_provisioning_rate: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+patch!(
+    /// A partial update to an `Organization`.
+    Organization => OrganizationPatch {
+        name: String,
+        description: String,
+        default: bool,
+        auto_off_threshold: Duration,
+        cpu_provisioning_rate: i64,
+    }
+);
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OrgMember {
     #[serde(flatten)]
@@ -27,14 +72,40 @@ pub struct OrgMember {
     pub roles_updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum OrgRole {
-    #[serde(rename = "organization-admin")]
-    Admin,
-    #[serde(rename = "organization-manager")]
-    Manager,
-    #[serde(rename = "registry-manager")]
-    RegistryManager,
-    #[serde(rename = "organization-member")]
-    Member,
+string_enum!(
+    pub enum OrgRole {
+        Admin = "organization-admin",
+        Manager = "organization-manager",
+        RegistryManager = "registry-manager",
+        Member = "organization-member",
+    }
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ORG: &str = r#"{
+        "id": "default",
+        "name": "Default",
+        "description": "The default organization.",
+        "default": true,
+        "members": [],
+        "environment_count": 3,
+        "resource_namespace": "coder",
+        "auto_off_threshold": 28800000,
+        "cpu_provisioning_rate": 4,
+        "created_at": "2020-05-29T18:10:33.532351Z",
+        "updated_at": "2020-05-29T18:10:33.532351Z"
+    }"#;
+
+    #[test]
+    fn test_org_auto_off_threshold() {
+        let org: Organization = serde_json::from_str(ORG).unwrap();
+        assert_eq!(org.auto_off_threshold, "8h".parse().unwrap());
+        assert_eq!(
+            serde_json::to_value(&org).unwrap(),
+            serde_json::from_str::<serde_json::Value>(ORG).unwrap()
+        );
+    }
 }
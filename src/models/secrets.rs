@@ -0,0 +1,87 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A sensitive string value, such as a secret or a private key. The value is redacted when
+/// formatted with `Debug`.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SecretValue(String);
+
+impl SecretValue {
+    /// Returns the underlying secret value.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"<redacted>\"")
+    }
+}
+
+impl From<String> for SecretValue {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+impl From<&str> for SecretValue {
+    fn from(s: &str) -> Self {
+        Self(s.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SecretId(pub String);
+// Neither the API docs nor the responses these models were written against pin down the format
+// of secret ids, which may be secret names, so any single path segment is accepted rather than
+// only object ids.
+id_string!(SecretId => segment);
+
+/// A per-user secret, usable as an environment variable inside the user's environments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Secret {
+    pub id: SecretId,
+    pub name: String,
+    /// Only returned when querying a single secret by its id.
+    pub value: Option<SecretValue>,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request body for creating or updating a secret.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecretRequest {
+    pub name: String,
+    pub value: SecretValue,
+    pub description: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_debug_redacts_value() {
+        let req = SecretRequest {
+            name: "GITHUB_TOKEN".into(),
+            value: "hunter2".into(),
+            description: "".into(),
+        };
+
+        let out = format!("{:?}", req);
+        assert!(out.contains("GITHUB_TOKEN"));
+        assert!(!out.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_serialize_value() {
+        let v = SecretValue::from("hunter2");
+        assert_eq!(serde_json::to_string(&v).unwrap(), "\"hunter2\"");
+        assert_eq!(v.expose(), "hunter2");
+    }
+}
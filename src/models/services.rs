@@ -1,26 +1,70 @@
 use super::*;
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ServiceId(pub String);
+id_string!(ServiceId => object_id);
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Service {
-    pub id: String,
+    pub id: ServiceId,
     pub name: String,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub description: String,
-    pub image_id: String,
+    pub image_id: ImageId,
     pub image_tag: String,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub command: String,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub args: Vec<String>,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub privileged: bool,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub volume_mounts: Vec<ServiceVolumeMount>,
+    #[cfg_attr(
+        not(feature = "strict"),
+        serde(default, deserialize_with = "compat::or_default")
+    )]
     pub env_vars: Vec<ServiceEnvVar>,
 }
 
+patch!(
+    /// A partial update to a `Service`.
+    Service => ServicePatch {
+        name: String,
+        description: String,
+        image_id: ImageId,
+        image_tag: String,
+        command: String,
+        args: Vec<String>,
+        privileged: bool,
+        volume_mounts: Vec<ServiceVolumeMount>,
+        env_vars: Vec<ServiceEnvVar>,
+    }
+);
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ServiceVolumeMount {
     pub name: String,
-    pub service_id: String,
+    pub service_id: ServiceId,
     pub path: String,
-    pub size_gb: i32,
+    #[serde(with = "gib")]
+    pub size_gb: ByteSize,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
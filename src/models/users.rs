@@ -1,9 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UserId(pub String);
+id_string!(UserId => object_id);
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct User {
-    pub id: String,
+    pub id: UserId,
     pub name: String,
     pub username: String,
     pub email: String,
@@ -15,14 +19,94 @@ pub struct User {
     pub updated_at: DateTime<Utc>,
 }
 
+patch!(
+    /// A partial update to a `User`.
+    User => UserPatch {
+        name: String,
+        username: String,
+        email: String,
+        dotfiles_git_uri: String,
+        roles: Vec<SiteRole>,
+    }
+);
+
+string_enum!(
+    pub enum SiteRole {
+        Admin = "site-admin",
+        Auditor = "site-auditor",
+        Manager = "site-manager",
+        Member = "site-member",
+    }
+);
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum SiteRole {
-    #[serde(rename = "site-admin")]
-    Admin,
-    #[serde(rename = "site-auditor")]
-    Auditor,
-    #[serde(rename = "site-manager")]
-    Manager,
-    #[serde(rename = "site-member")]
-    Member,
+pub struct SshKey {
+    pub public_key: String,
+    /// Only returned to the owner of the key.
+    pub private_key: Option<super::SecretValue>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SiteRole, User, UserPatch};
+
+    fn user() -> User {
+        serde_json::from_str(
+            r#"{
+                "id": "5e876cf4-10abe9b2e54eb609c5ec1870",
+                "name": "Colin Adler",
+                "username": "colin",
+                "email": "colin@coder.com",
+                "dotfiles_git_uri": "",
+                "roles": ["site-member"],
+                "avatar_hash": "28707dc83fdcba2cacaa3ad5e381b34b7cb37b74",
+                "key_regenerated_at": "2020-04-03T17:05:56.964782Z",
+                "created_at": "2020-04-03T17:05:56.964782Z",
+                "updated_at": "2020-05-29T18:10:33.532351Z"
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_patch_diff() {
+        let old = user();
+        let mut new = old.clone();
+        assert!(UserPatch::diff(&old, &new).is_empty());
+
+        new.email = "colin@example.com".into();
+        new.roles.push(SiteRole::Auditor);
+        let patch = UserPatch::diff(&old, &new);
+
+        assert_eq!(patch.email.as_deref(), Some("colin@example.com"));
+        assert_eq!(patch.roles, Some(vec![SiteRole::Member, SiteRole::Auditor]));
+        assert!(patch.name.is_none());
+        assert_eq!(
+            serde_json::to_value(&patch).unwrap(),
+            serde_json::json!({
+                "email": "colin@example.com",
+                "roles": ["site-member", "site-auditor"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_known_role() {
+        let r: SiteRole = serde_json::from_str("\"site-auditor\"").unwrap();
+        assert_eq!(r, SiteRole::Auditor);
+        assert_eq!(serde_json::to_string(&r).unwrap(), "\"site-auditor\"");
+    }
+
+    #[test]
+    fn test_unknown_role_round_trips() {
+        let r: SiteRole = serde_json::from_str("\"site-janitor\"").unwrap();
+        assert_eq!(r, SiteRole::Unknown("site-janitor".into()));
+        assert_eq!(serde_json::to_string(&r).unwrap(), "\"site-janitor\"");
+    }
+
+    #[test]
+    fn test_display_from_str() {
+        assert_eq!(SiteRole::Manager.to_string(), "site-manager");
+        assert_eq!("site-member".parse::<SiteRole>().unwrap(), SiteRole::Member);
+    }
 }
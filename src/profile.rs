@@ -0,0 +1,450 @@
+//! Named profiles for working with several deployments.
+//!
+//! Profiles are read from `CODER_PROFILES` if it's set, or from `coder-rs/profiles.toml` in the
+//! user config directory, such as `~/.config/coder-rs/profiles.toml` on Linux. Each table is a
+//! profile:
+//!
+//! ```toml
+//! [staging]
+//! url = "https://staging.coder.example.com"
+//! token = { env = "CODER_STAGING_TOKEN" }
+//!
+//! [prod]
+//! url = "https://coder.example.com"
+//! token = { command = ["pass", "show", "coder/prod"] }
+//! proxy = "http://proxy.corp.example.com:3128"
+//! no_proxy = "localhost,.internal.example.com"
+//!
+//! [customer]
+//! url = "https://coder.customer.example.com"
+//! token = { file = "~/.config/coder-rs/customer.token" }
+//! tls = { ca_file = "~/.config/coder-rs/customer-ca.pem" }
+//! ```
+//!
+//! Tokens are never stored in the profile itself. They're read from an environment variable, a
+//! file, or the output of a command when the client is created.
+//!
+//! ```rust,no_run
+//! # fn run() -> Result<(), coder::Error> {
+//! use coder::Coder;
+//!
+//! let c = Coder::from_profile("staging")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::Deserialize;
+
+use crate::client::Coder;
+use crate::config::read_config_file;
+use crate::error::{ConfigError, Error};
+use crate::transport::{HyperTransport, Proxy};
+
+/// The environment variable that overrides the path of the profiles file.
+pub const PROFILES_ENV: &str = "CODER_PROFILES";
+
+impl Coder {
+    /// Creates a client from the named profile in the default profiles file. See
+    /// [`Profiles::load`].
+    pub fn from_profile(name: &str) -> Result<Self, Error> {
+        Profiles::load()?.client(name)
+    }
+}
+
+/// The profiles in a profiles file.
+#[derive(Debug, Clone)]
+pub struct Profiles {
+    path: PathBuf,
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    /// Loads profiles from `CODER_PROFILES` if it's set, or from `coder-rs/profiles.toml` in the
+    /// user config directory.
+    pub fn load() -> Result<Self, Error> {
+        let path = match std::env::var_os(PROFILES_ENV) {
+            Some(path) if !path.is_empty() => PathBuf::from(path),
+            _ => dirs::config_dir()
+                .ok_or(ConfigError::NoConfigDir)?
+                .join("coder-rs")
+                .join("profiles.toml"),
+        };
+        Self::from_path(path)
+    }
+
+    /// Loads profiles from the TOML file at `path`.
+    pub fn from_path<P: Into<PathBuf>>(path: P) -> Result<Self, Error> {
+        let path = path.into();
+        let contents = std::fs::read_to_string(&path).map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => ConfigError::MissingFile(path.clone()),
+            _ => ConfigError::ReadFile {
+                path: path.clone(),
+                source: err,
+            },
+        })?;
+        Self::parse(path, &contents)
+    }
+
+    fn parse(path: PathBuf, contents: &str) -> Result<Self, Error> {
+        match toml::from_str(contents) {
+            Ok(profiles) => Ok(Self { path, profiles }),
+            Err(source) => Err(ConfigError::ParseProfiles { path, source }.into()),
+        }
+    }
+
+    /// Returns the path the profiles were loaded from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the profile with the given name.
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.get(name)
+    }
+
+    /// Returns the names of all profiles, in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Creates a client from the profile with the given name.
+    pub fn client(&self, name: &str) -> Result<Coder, Error> {
+        self.get(name)
+            .ok_or_else(|| ConfigError::UnknownProfile {
+                name: name.to_string(),
+                path: self.path.clone(),
+            })?
+            .client()
+    }
+}
+
+/// A deployment to connect to.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// The manager's base URL.
+    pub url: String,
+    /// Where to read the session token from.
+    #[serde(deserialize_with = "token_source")]
+    pub token: TokenSource,
+    /// The proxy to send requests through. The proxy environment variables are used if it isn't
+    /// set. See [`Proxy::all`] for the URLs that are supported.
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Hosts to reach without a proxy, in the format of `NO_PROXY`. Applies to
+    /// [`proxy`](Self::proxy), or to the proxy environment variables if it isn't set, replacing
+    /// `NO_PROXY`.
+    #[serde(default)]
+    pub no_proxy: Option<String>,
+    #[serde(default)]
+    pub tls: TlsConfig,
+}
+
+impl Profile {
+    /// Creates a client for the profile, reading its token. Proxy and TLS settings don't apply to
+    /// `unix://` URLs.
+    pub fn client(&self) -> Result<Coder, Error> {
+        let token = self.token.read()?;
+        if self.url.starts_with("unix:") {
+            return Coder::new(self.url.clone(), token);
+        }
+
+        let mut builder = HyperTransport::builder();
+        if let Some(proxy) = self.proxy(Proxy::from_env)? {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(ca_file) = &self.tls.ca_file {
+            let path = expand_home(ca_file);
+            let pem = std::fs::read(&path).map_err(|err| ConfigError::ReadFile {
+                path: path.clone(),
+                source: err,
+            })?;
+            builder =
+                builder
+                    .add_root_certificates(&pem)
+                    .map_err(|err| ConfigError::InvalidCaFile {
+                        path,
+                        reason: err.to_string(),
+                    })?;
+        }
+        Coder::with_transport(self.url.clone(), token, builder.build())
+    }
+
+    /// Returns the proxy settings to use, or `None` to use the environment as usual.
+    fn proxy(
+        &self,
+        from_env: impl FnOnce() -> Result<Option<Proxy>, Error>,
+    ) -> Result<Option<Proxy>, Error> {
+        let proxy = match (&self.proxy, &self.no_proxy) {
+            (Some(proxy), _) => Proxy::all(proxy)?,
+            (None, Some(_)) => match from_env()? {
+                Some(proxy) => proxy,
+                None => return Ok(None),
+            },
+            (None, None) => return Ok(None),
+        };
+        Ok(Some(match &self.no_proxy {
+            Some(no_proxy) => proxy.no_proxy(no_proxy),
+            None => proxy,
+        }))
+    }
+}
+
+/// Where a profile's session token is read from. File paths may start with `~/`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenSource {
+    /// Reads the token from an environment variable.
+    Env(String),
+    /// Reads the token from a file, ignoring surrounding whitespace.
+    File(PathBuf),
+    /// Runs a command, given as the program and its arguments, and reads the token from its
+    /// output. The command inherits stdin and stderr, so it can prompt for a passphrase.
+    Command(Vec<String>),
+}
+
+/// Deserializes a [`TokenSource`], rejecting inline tokens without echoing them in the error.
+fn token_source<'de, D: serde::Deserializer<'de>>(de: D) -> Result<TokenSource, D::Error> {
+    struct Visitor;
+
+    impl<'de> serde::de::Visitor<'de> for Visitor {
+        type Value = TokenSource;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a table with one of `env`, `file` or `command`")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, _: &str) -> Result<TokenSource, E> {
+            Err(E::custom(
+                "inline tokens aren't supported, use `env`, `file` or `command` instead",
+            ))
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<TokenSource, A::Error> {
+            TokenSource::deserialize(serde::de::value::MapAccessDeserializer::new(map))
+        }
+    }
+
+    de.deserialize_any(Visitor)
+}
+
+impl TokenSource {
+    /// Reads the token.
+    pub fn read(&self) -> Result<String, ConfigError> {
+        match self {
+            TokenSource::Env(name) => match std::env::var(name) {
+                Ok(token) if !token.is_empty() => Ok(token),
+                Err(std::env::VarError::NotUnicode(_)) => {
+                    Err(ConfigError::InvalidEnv(name.clone()))
+                }
+                _ => Err(ConfigError::MissingEnv(name.clone())),
+            },
+            TokenSource::File(path) => read_config_file(&expand_home(path)),
+            TokenSource::Command(args) => run_token_command(args),
+        }
+    }
+}
+
+fn run_token_command(args: &[String]) -> Result<String, ConfigError> {
+    let err = |reason: String| ConfigError::TokenCommand {
+        command: args.join(" "),
+        reason,
+    };
+    let (program, args) = args
+        .split_first()
+        .ok_or_else(|| err("no program given".into()))?;
+
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| err(e.to_string()))?;
+    if !output.status.success() {
+        return Err(err(output.status.to_string()));
+    }
+    let token = String::from_utf8(output.stdout).map_err(|_| err("output isn't UTF-8".into()))?;
+    match token.trim() {
+        "" => Err(err("printed an empty token".into())),
+        token => Ok(token.to_string()),
+    }
+}
+
+/// TLS settings for a profile.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// A PEM bundle of CA certificates to trust in addition to the system's trusted roots. May
+    /// start with `~/`.
+    #[serde(default)]
+    pub ca_file: Option<PathBuf>,
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PROFILES: &str = r#"
+        [staging]
+        url = "https://staging.coder.example.com"
+        token = { env = "CODER_RS_TEST_STAGING_TOKEN" }
+
+        [prod]
+        url = "https://coder.example.com"
+        token = { command = ["echo", "prod-token"] }
+        proxy = "http://proxy.corp.example.com:3128"
+        no_proxy = "localhost"
+
+        [customer]
+        url = "https://coder.customer.example.com"
+        token = { file = "~/customer.token" }
+        tls = { ca_file = "/nonexistent/ca.pem" }
+    "#;
+
+    fn profiles() -> Profiles {
+        Profiles::parse("profiles.toml".into(), PROFILES).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let p = profiles();
+        assert_eq!(
+            p.names().collect::<Vec<_>>(),
+            vec!["customer", "prod", "staging"]
+        );
+
+        let prod = p.get("prod").unwrap();
+        assert_eq!(prod.url, "https://coder.example.com");
+        assert_eq!(
+            prod.token,
+            TokenSource::Command(vec!["echo".into(), "prod-token".into()])
+        );
+        assert_eq!(prod.no_proxy.as_deref(), Some("localhost"));
+
+        let customer = p.get("customer").unwrap();
+        assert_eq!(customer.token, TokenSource::File("~/customer.token".into()));
+        assert_eq!(
+            customer.tls.ca_file.as_deref(),
+            Some(Path::new("/nonexistent/ca.pem"))
+        );
+        assert_eq!(
+            expand_home(Path::new("~/customer.token")),
+            dirs::home_dir().unwrap().join("customer.token")
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for contents in &[
+            "[a]\nurl = \"https://coder.example.com\"",
+            "[a]\nurl = \"https://coder.example.com\"\ntoken = \"inline secret\"",
+            "[a]\nurl = \"https://coder.example.com\"\ntoken = { env = \"X\" }\ncolor = true",
+        ] {
+            let err = Profiles::parse("profiles.toml".into(), contents).unwrap_err();
+            assert!(
+                matches!(err, Error::Config(ConfigError::ParseProfiles { .. })),
+                "{}",
+                contents
+            );
+            assert!(!err.to_string().contains("inline secret"));
+        }
+    }
+
+    #[test]
+    fn test_unknown_profile() {
+        let err = profiles().client("dev").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "config error: no profile named \"dev\" in profiles.toml"
+        );
+    }
+
+    #[test]
+    fn test_token_sources() {
+        let err = profiles().client("staging").err().unwrap();
+        assert!(
+            matches!(&err, Error::Config(ConfigError::MissingEnv(name)) if name == "CODER_RS_TEST_STAGING_TOKEN"),
+            "{}",
+            err
+        );
+
+        let c = profiles().client("prod").unwrap();
+        assert_eq!(c.token, "prod-token");
+
+        let err = TokenSource::Command(vec!["false".into()])
+            .read()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::TokenCommand { .. }));
+        let err = TokenSource::Command(Vec::new()).read().unwrap_err();
+        assert!(matches!(err, ConfigError::TokenCommand { .. }));
+
+        let dir = std::env::temp_dir().join(format!("coder-rs-profile-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("token");
+        std::fs::write(&path, "file-token\n").unwrap();
+        assert_eq!(TokenSource::File(path).read().unwrap(), "file-token");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_no_proxy_from_env() {
+        let from_env = || {
+            Proxy::from_vars(|key| match key {
+                "HTTPS_PROXY" => Some("http://env-proxy:3128".into()),
+                "NO_PROXY" => Some("internal.example.com".into()),
+                _ => None,
+            })
+        };
+        let uri = |s: &str| s.parse::<http::Uri>().unwrap();
+
+        let mut profile = profiles().get("prod").unwrap().clone();
+        profile.proxy = None;
+        let proxy = profile.proxy(from_env).unwrap().unwrap();
+        assert!(proxy.intercept(&uri("https://localhost")).is_none());
+        assert!(proxy.intercept(&uri("https://coder.example.com")).is_some());
+        // The profile's list replaces `NO_PROXY`.
+        assert!(proxy
+            .intercept(&uri("https://internal.example.com"))
+            .is_some());
+
+        profile.no_proxy = None;
+        assert!(profile.proxy(from_env).unwrap().is_none());
+        assert!(profile.proxy(|| Ok(None)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_tls_config() {
+        let mut profile = profiles().get("prod").unwrap().clone();
+        profile.tls.ca_file = Some("/nonexistent/ca.pem".into());
+        let err = profile.client().err().unwrap();
+        assert!(
+            matches!(err, Error::Config(ConfigError::ReadFile { .. })),
+            "{}",
+            err
+        );
+
+        let dir = std::env::temp_dir().join(format!("coder-rs-ca-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ca.pem");
+        std::fs::write(&path, "not a certificate").unwrap();
+        profile.tls.ca_file = Some(path);
+        let err = profile.client().err().unwrap();
+        assert!(
+            matches!(err, Error::Config(ConfigError::InvalidCaFile { .. })),
+            "{}",
+            err
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+//! Requests to routes that don't have a request builder yet.
+//!
+//! [`Coder::request`] creates a [`RequestBuilder`] for any method and API path. Requests are sent
+//! with the same authentication, headers and middleware as the generated request builders, and
+//! responses are parsed into an [`ApiResponse`] the same way. Traces and metrics group them under
+//! the [`RAW_ROUTE`] route template unless one is set with [`RequestBuilder::route`].
+//!
+//! ```rust,no_run
+//! # async fn run() -> Result<(), coder::Error> {
+//! use coder::{Coder, Method};
+//!
+//! let c = Coder::new("https://coder.example.com".into(), "api key")?;
+//! let res = c
+//!     .request(Method::GET, "/orgs/default/members")?
+//!     .route("/orgs/:id/members")
+//!     .query("limit", 10)
+//!     .send::<serde_json::Value>()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::convert::TryInto;
+use std::sync::Arc;
+
+use http::header::{HeaderName, HeaderValue};
+use http::Method;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::builder::{validate, Builder};
+use crate::client::{ApiResponse, Coder, RawResponse};
+use crate::error::Error;
+use crate::transport::Transport;
+
+/// The route template recorded for raw requests that don't set one with
+/// [`RequestBuilder::route`].
+pub const RAW_ROUTE: &str = "raw";
+
+/// A request to an arbitrary API route, created by [`Coder::request`]. Like other request
+/// builders it can be cloned and sent any number of times.
+#[derive(Clone)]
+pub struct RequestBuilder {
+    pub(crate) builder: Builder,
+    pub(crate) client: Arc<dyn Transport>,
+}
+
+impl Coder {
+    /// Begins a request to an API route that doesn't have a request builder. The path is relative
+    /// to the API root, so `"/users/me"` requests `/api/users/me`. Each path segment is escaped, and
+    /// segments such as `..` that would change the route are rejected.
+    pub fn request(&self, method: Method, path: &str) -> Result<RequestBuilder, Error> {
+        let mut req = self.new_base_request();
+        *req.method_mut() = method;
+        let mut b = RequestBuilder {
+            builder: Builder::new(self.url.clone(), req),
+            client: Arc::clone(&self.client),
+        };
+        let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+        for s in &segments {
+            validate::segment("path segment", s)?;
+        }
+        join_path!(b, &segments, &[])?;
+        b.builder.route = RAW_ROUTE.into();
+        Ok(b)
+    }
+}
+
+impl RequestBuilder {
+    /// Sets the route template recorded for the request in traces and metrics, such as
+    /// `/users/:id`. Raw requests use [`RAW_ROUTE`] by default, rather than their path, so ids in
+    /// the path don't create a separate route for every resource.
+    pub fn route(mut self, template: &str) -> Self {
+        self.builder.route = template.to_string();
+        self
+    }
+
+    /// Appends a query parameter. Parameters with the same key may be given more than once.
+    pub fn query<V: ToString>(mut self, key: &str, value: V) -> Self {
+        self.builder
+            .url
+            .query_pairs_mut()
+            .append_pair(key, &value.to_string());
+        self
+    }
+
+    /// Sets a request header, replacing any existing value. Returns an error if the header name or
+    /// value is invalid.
+    pub fn header<K, V>(mut self, key: K, value: V) -> Result<Self, Error>
+    where
+        K: TryInto<HeaderName>,
+        V: TryInto<HeaderValue>,
+        http::Error: From<K::Error> + From<V::Error>,
+    {
+        let key = key.try_into().map_err(http::Error::from)?;
+        let value = value.try_into().map_err(http::Error::from)?;
+        self.builder.req.headers_mut().insert(key, value);
+        Ok(self)
+    }
+
+    /// Sets a JSON request body. Returns an error if the body can't be serialized.
+    pub fn json<B: Serialize + ?Sized>(mut self, body: &B) -> Result<Self, Error> {
+        join_body!(self, body);
+        Ok(self)
+    }
+
+    /// Sends the request and deserializes a successful response into `T`.
+    pub async fn send<T: DeserializeOwned>(&self) -> Result<ApiResponse<T>, Error> {
+        crate::client::execute(&*self.client, self.builder.build()?).await
+    }
+
+    /// Sends the request and returns the response without deserializing its body.
+    pub async fn send_raw(&self) -> Result<RawResponse, Error> {
+        crate::client::execute_raw(&*self.client, self.builder.build()?).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::Route;
+    use crate::models::EnvironmentPatch;
+    use crate::transport::test::MockTransport;
+
+    #[tokio::test]
+    async fn test_raw_request() {
+        let t = MockTransport::new(200, r#"{"ok":true}"#);
+        let c =
+            Coder::with_transport("https://coder.example.com".into(), "key", t.clone()).unwrap();
+
+        let patch = EnvironmentPatch {
+            name: Some("dev".into()),
+            ..Default::default()
+        };
+        let res = c
+            .request(Method::PATCH, "/environments/some-id/")
+            .unwrap()
+            .query("force", true)
+            .query("tag", "a")
+            .query("tag", "b&c")
+            .header("X-Team", "platform")
+            .unwrap()
+            .json(&patch)
+            .unwrap()
+            .send::<serde_json::Value>()
+            .await
+            .expect("send request");
+        assert_eq!(res.response.unwrap()["ok"], true);
+
+        let reqs = t.requests.lock().unwrap();
+        let req = &reqs[0];
+        assert_eq!(req.method(), Method::PATCH);
+        assert_eq!(
+            req.uri(),
+            "https://coder.example.com/api/environments/some-id?force=true&tag=a&tag=b%26c"
+        );
+        assert_eq!(req.headers()["Session-Token"], "key");
+        assert_eq!(req.headers()["X-Team"], "platform");
+        assert_eq!(req.headers()["Content-Type"], "application/json");
+        assert_eq!(req.body().as_ref(), br#"{"name":"dev"}"#);
+        assert_eq!(req.extensions().get::<Route>().unwrap().as_str(), RAW_ROUTE);
+    }
+
+    #[tokio::test]
+    async fn test_raw_request_route() {
+        let t = MockTransport::new(200, "null");
+        let c =
+            Coder::with_transport("https://coder.example.com".into(), "key", t.clone()).unwrap();
+
+        c.request(Method::GET, "/users/5e876cf4-10abe9b2e54eb609c5ec1870")
+            .unwrap()
+            .route("/users/:id")
+            .send_raw()
+            .await
+            .expect("send request");
+
+        let reqs = t.requests.lock().unwrap();
+        assert_eq!(
+            reqs[0].extensions().get::<Route>().unwrap().as_str(),
+            "/users/:id"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_raw_request_errors() {
+        let t = MockTransport::new(403, r#"{"error":{"msg":"forbidden","code":"forbidden"}}"#);
+        let c =
+            Coder::with_transport("https://coder.example.com".into(), "key", t.clone()).unwrap();
+
+        let res = c
+            .request(Method::DELETE, "/users/me")
+            .unwrap()
+            .send::<()>()
+            .await
+            .expect("send request");
+        assert_eq!(res.response.unwrap_err().error.code, "forbidden");
+
+        let res = c
+            .request(Method::GET, "/users/me")
+            .unwrap()
+            .header("Bad Header", "value");
+        assert!(matches!(res, Err(Error::HyperHttp(_))));
+        assert_eq!(t.requests.lock().unwrap().len(), 1);
+    }
+}
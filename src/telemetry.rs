@@ -0,0 +1,302 @@
+//! Request metrics recorded through the [`metrics`] facade. All metrics are labeled with the
+//! request `method` and `route` template, so they can be collected by any `metrics` exporter.
+//!
+//! * `coder_requests_total` - Counter of completed requests, also labeled with the response
+//!   `status`.
+//! * `coder_request_errors_total` - Counter of failed requests, also labeled with the error
+//!   `code`. API errors use `ApiErrorInner.code`, or `unknown` when the manager didn't send one.
+//!   Requests that didn't get a response use `transport`, and responses that couldn't be
+//!   deserialized use `deserialize`.
+//! * `coder_request_duration_seconds` - Histogram of request latencies.
+//! * `coder_requests_in_flight` - Gauge of requests that have been sent but not yet completed.
+
+use std::time::Instant;
+
+use crate::client::{ApiResponse, RawResponse, Route};
+use crate::error::{ApiError, Error};
+use crate::transport::Bytes;
+use http::{Request, StatusCode};
+use serde::de::DeserializeOwned;
+
+/// A parsed response whose API error code can be recorded.
+pub(crate) trait ErrorCode {
+    /// Returns the error code of a failed request, or `None` if it succeeded.
+    fn error_code(&self) -> Option<String>;
+}
+
+impl<T: DeserializeOwned> ErrorCode for ApiResponse<T> {
+    fn error_code(&self) -> Option<String> {
+        self.response
+            .as_ref()
+            .err()
+            .map(|err| err.error.code.clone())
+    }
+}
+
+impl ErrorCode for RawResponse {
+    fn error_code(&self) -> Option<String> {
+        if self.status_code.is_success() {
+            return None;
+        }
+        let code = serde_json::from_slice::<ApiError>(&self.body)
+            .map(|err| err.error.code)
+            .unwrap_or_default();
+        Some(code)
+    }
+}
+
+/// Tracks a single request from when it is sent until it completes or is dropped.
+pub(crate) struct RequestMetrics {
+    method: String,
+    route: String,
+    start: Instant,
+}
+
+impl RequestMetrics {
+    pub(crate) fn start(req: &Request<Bytes>) -> Self {
+        let m = Self {
+            method: req.method().to_string(),
+            route: req
+                .extensions()
+                .get::<Route>()
+                .map(Route::to_string)
+                .unwrap_or_else(|| "/".into()),
+            start: Instant::now(),
+        };
+        m.in_flight().increment(1.0);
+        m
+    }
+
+    fn in_flight(&self) -> metrics::Gauge {
+        metrics::gauge!(
+            "coder_requests_in_flight",
+            "method" => self.method.clone(),
+            "route" => self.route.clone(),
+        )
+    }
+
+    fn error(&self, code: String) {
+        metrics::counter!(
+            "coder_request_errors_total",
+            "method" => self.method.clone(),
+            "route" => self.route.clone(),
+            "code" => code,
+        )
+        .increment(1);
+    }
+
+    /// Records a completed request. `status` is the status of the response, if one was received,
+    /// and `res` is the result of parsing it. At most one error is recorded per request.
+    pub(crate) fn finish<R: ErrorCode>(&self, status: Option<StatusCode>, res: &Result<R, Error>) {
+        metrics::histogram!(
+            "coder_request_duration_seconds",
+            "method" => self.method.clone(),
+            "route" => self.route.clone(),
+        )
+        .record(self.start.elapsed().as_secs_f64());
+
+        if let Some(status) = status {
+            metrics::counter!(
+                "coder_requests_total",
+                "method" => self.method.clone(),
+                "route" => self.route.clone(),
+                "status" => status.as_u16().to_string(),
+            )
+            .increment(1);
+        }
+
+        let code = match (res, status) {
+            (Ok(res), _) => res.error_code().map(|code| match code.as_str() {
+                "" => "unknown".to_string(),
+                _ => code,
+            }),
+            (Err(_), None) => Some("transport".into()),
+            (Err(_), Some(_)) => Some("deserialize".into()),
+        };
+        if let Some(code) = code {
+            self.error(code);
+        }
+    }
+}
+
+impl Drop for RequestMetrics {
+    fn drop(&mut self) {
+        self.in_flight().decrement(1.0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use metrics::{
+        Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata,
+        Recorder, SharedString, Unit,
+    };
+
+    use crate::client::Executor;
+    use crate::transport::test::MockTransport;
+    use crate::Coder;
+
+    /// Records every metric update as the sum of its values, keyed by name and labels.
+    #[derive(Default)]
+    struct TestRecorder {
+        values: Arc<Mutex<HashMap<String, f64>>>,
+    }
+
+    struct Handle {
+        key: String,
+        values: Arc<Mutex<HashMap<String, f64>>>,
+    }
+
+    impl Handle {
+        fn add(&self, v: f64) {
+            *self
+                .values
+                .lock()
+                .unwrap()
+                .entry(self.key.clone())
+                .or_default() += v;
+        }
+    }
+
+    impl CounterFn for Handle {
+        fn increment(&self, v: u64) {
+            self.add(v as f64);
+        }
+
+        fn absolute(&self, _: u64) {}
+    }
+
+    impl GaugeFn for Handle {
+        fn increment(&self, v: f64) {
+            self.add(v);
+        }
+
+        fn decrement(&self, v: f64) {
+            self.add(-v);
+        }
+
+        fn set(&self, _: f64) {}
+    }
+
+    impl HistogramFn for Handle {
+        fn record(&self, _: f64) {
+            self.add(1.0);
+        }
+    }
+
+    impl TestRecorder {
+        fn handle(&self, key: &Key) -> Arc<Handle> {
+            let mut labels: Vec<_> = key
+                .labels()
+                .map(|l| format!("{}={}", l.key(), l.value()))
+                .collect();
+            labels.sort();
+            Arc::new(Handle {
+                key: format!("{}{{{}}}", key.name(), labels.join(",")),
+                values: Arc::clone(&self.values),
+            })
+        }
+
+        fn get(&self, key: &str) -> f64 {
+            self.values
+                .lock()
+                .unwrap()
+                .get(key)
+                .copied()
+                .unwrap_or_default()
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.handle(key))
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::from_arc(self.handle(key))
+        }
+
+        fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::from_arc(self.handle(key))
+        }
+    }
+
+    fn run(status: u16, body: &'static str, rec: &TestRecorder) -> Result<(), crate::Error> {
+        let t = MockTransport::new(status, body);
+        let c = Coder::with_transport("https://coder.example.com".into(), "key", t).unwrap();
+
+        let mut rt = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .unwrap();
+        metrics::with_local_recorder(rec, || {
+            rt.block_on(async { c.orgs().namespaces().execute().await })
+        })
+        .map(|_| ())
+    }
+
+    #[test]
+    fn test_request_metrics() {
+        let rec = TestRecorder::default();
+        run(200, "[]", &rec).expect("send request");
+        run(
+            404,
+            r#"{"error":{"msg":"user not found","code":"not_found"}}"#,
+            &rec,
+        )
+        .expect("send request");
+
+        let labels = "method=GET,route=/orgs/namespaces";
+        assert_eq!(
+            rec.get(&format!("coder_requests_total{{{},status=200}}", labels)),
+            1.0
+        );
+        assert_eq!(
+            rec.get(&format!("coder_requests_total{{{},status=404}}", labels)),
+            1.0
+        );
+        assert_eq!(
+            rec.get(&format!(
+                "coder_request_errors_total{{code=not_found,{}}}",
+                labels
+            )),
+            1.0
+        );
+        assert_eq!(
+            rec.get(&format!("coder_request_duration_seconds{{{}}}", labels)),
+            2.0
+        );
+        assert_eq!(
+            rec.get(&format!("coder_requests_in_flight{{{}}}", labels)),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_request_metrics_single_error() {
+        let rec = TestRecorder::default();
+        run(502, "<html>Bad Gateway</html>", &rec).unwrap_err();
+
+        let labels = "method=GET,route=/orgs/namespaces";
+        let errors = |code| {
+            rec.get(&format!(
+                "coder_request_errors_total{{code={},{}}}",
+                code, labels
+            ))
+        };
+        assert_eq!(errors("deserialize"), 1.0);
+        assert_eq!(errors("unknown"), 0.0);
+        assert_eq!(
+            rec.get(&format!("coder_requests_total{{{},status=502}}", labels)),
+            1.0
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! Hostname resolution for [`HyperTransport`](super::HyperTransport).
+//!
+//! Resolvers only choose the addresses that are connected to. Requests keep the hostname from the
+//! base URL, so it's still used for TLS SNI, certificate verification and the `Host` header.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use hyper::client::connect::dns::{GaiResolver, Name};
+use tower_service::Service;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Resolves hostnames to IP addresses, replacing the system resolver.
+///
+/// ```rust
+/// use std::net::IpAddr;
+///
+/// use coder::transport::{HyperTransport, Resolve};
+///
+/// /// Sends every request to the blue manager node.
+/// struct Blue;
+///
+/// #[async_trait::async_trait]
+/// impl Resolve for Blue {
+///     async fn resolve(
+///         &self,
+///         _host: &str,
+///     ) -> Result<Vec<IpAddr>, Box<dyn std::error::Error + Send + Sync>> {
+///         Ok(vec!["10.0.0.1".parse()?])
+///     }
+/// }
+///
+/// let transport = HyperTransport::builder().resolver(Blue).build();
+/// ```
+#[async_trait]
+pub trait Resolve: Send + Sync {
+    /// Returns the addresses of `host`, which are tried in order.
+    async fn resolve(&self, host: &str) -> Result<Vec<IpAddr>, BoxError>;
+}
+
+#[async_trait]
+impl<T: Resolve + ?Sized> Resolve for Arc<T> {
+    async fn resolve(&self, host: &str) -> Result<Vec<IpAddr>, BoxError> {
+        (**self).resolve(host).await
+    }
+}
+
+/// Resolves hostnames with static overrides, then a custom resolver if one is set, then the
+/// system resolver.
+#[derive(Clone)]
+pub(crate) struct Resolver {
+    overrides: Arc<HashMap<String, Vec<IpAddr>>>,
+    custom: Option<Arc<dyn Resolve>>,
+    system: GaiResolver,
+}
+
+impl Resolver {
+    pub(crate) fn new(
+        overrides: HashMap<String, Vec<IpAddr>>,
+        custom: Option<Arc<dyn Resolve>>,
+    ) -> Self {
+        Self {
+            overrides: Arc::new(overrides),
+            custom,
+            system: GaiResolver::new(),
+        }
+    }
+
+    /// Returns the static overrides, keyed by lowercase hostname.
+    pub(crate) fn overrides(&self) -> Arc<HashMap<String, Vec<IpAddr>>> {
+        Arc::clone(&self.overrides)
+    }
+}
+
+impl Service<Name> for Resolver {
+    type Response = std::vec::IntoIter<IpAddr>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        self.system.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let host = name.as_str().to_ascii_lowercase();
+        if let Some(addrs) = self.overrides.get(&host) {
+            let addrs = addrs.clone();
+            return Box::pin(async move { Ok(addrs.into_iter()) });
+        }
+
+        match &self.custom {
+            Some(custom) => {
+                let custom = Arc::clone(custom);
+                Box::pin(async move {
+                    let addrs = custom.resolve(&host).await?;
+                    if addrs.is_empty() {
+                        return Err(format!("no addresses found for {}", host).into());
+                    }
+                    Ok(addrs.into_iter())
+                })
+            }
+            None => {
+                let res = self.system.call(name);
+                Box::pin(async move { Ok(res.await?.collect::<Vec<_>>().into_iter()) })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::transport::HyperTransport;
+    use crate::{Coder, Error, Executor};
+
+    /// Serves one request, returning the request head.
+    async fn server() -> (u16, tokio::task::JoinHandle<String>) {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = tokio::spawn(async move {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            let mut req = Vec::new();
+            let mut buf = [0; 1024];
+            while !req.ends_with(b"\r\n\r\n") {
+                let n = conn.read(&mut buf).await.unwrap();
+                req.extend_from_slice(&buf[..n]);
+            }
+            conn.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\n[\"default\"]")
+                .await
+                .unwrap();
+            String::from_utf8(req).unwrap()
+        });
+        (port, handle)
+    }
+
+    #[tokio::test]
+    async fn test_static_override() {
+        let (port, server) = server().await;
+        let t = HyperTransport::builder()
+            .no_proxy()
+            .resolve("Coder.Example.com", "127.0.0.1".parse().unwrap())
+            .build();
+        let url = format!("http://coder.example.com:{}", port);
+        let c = Coder::with_transport(url, "key", t).unwrap();
+
+        let res = c.orgs().namespaces().execute().await.unwrap();
+        assert_eq!(res.response.unwrap(), vec!["default".to_string()]);
+
+        let req = server.await.unwrap();
+        assert!(
+            req.contains(&format!("host: coder.example.com:{}\r\n", port)),
+            "{}",
+            req
+        );
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        hosts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Resolve for Recorder {
+        async fn resolve(&self, host: &str) -> Result<Vec<IpAddr>, BoxError> {
+            self.hosts.lock().unwrap().push(host.to_string());
+            match host {
+                "blue.example.com" => Ok(vec!["127.0.0.1".parse().unwrap()]),
+                "empty.example.com" => Ok(Vec::new()),
+                _ => Err("unknown host".into()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_resolver() {
+        let (port, server) = server().await;
+        let r = Arc::new(Recorder::default());
+        let t = HyperTransport::builder()
+            .no_proxy()
+            .resolver(r.clone())
+            .build();
+
+        let url = format!("http://blue.example.com:{}", port);
+        let c = Coder::with_transport(url, "key", t.clone()).unwrap();
+        c.orgs().namespaces().execute().await.unwrap();
+        server.await.unwrap();
+
+        for host in &["empty.example.com", "green.example.com"] {
+            let url = format!("http://{}:{}", host, port);
+            let c = Coder::with_transport(url, "key", t.clone()).unwrap();
+            let err = c.orgs().namespaces().execute().await.unwrap_err();
+            assert!(matches!(err, Error::Hyper(_)), "{}", err);
+        }
+        assert_eq!(
+            *r.hosts.lock().unwrap(),
+            vec!["blue.example.com", "empty.example.com", "green.example.com"]
+        );
+    }
+}
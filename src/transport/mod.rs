@@ -0,0 +1,385 @@
+//! Transports send fully built requests to the manager and return the raw responses.
+//!
+//! By default [`Coder`](crate::Coder) uses [`HyperTransport`]. A custom [`Transport`] can be
+//! provided with [`Coder::with_transport`](crate::Coder::with_transport), for example to send
+//! requests through an in-house HTTP stack or to return canned responses in tests.
+//!
+//! [`HyperTransport::new`] sends requests through the proxies set in the environment, if any. See
+//! [`Proxy::from_env`] for the variables that are read, and [`HyperTransport::builder`] to
+//! configure a proxy explicitly. The builder can also override DNS resolution, for example to
+//! target one node of a manager while keeping its hostname for TLS and the `Host` header.
+//!
+//! On Unix, [`Coder::new`](crate::Coder::new) uses [`UnixTransport`] for `unix://` base URLs.
+
+mod dns;
+mod proxy;
+#[cfg(unix)]
+mod unix;
+
+// TLS imports
+#[cfg(feature = "rustls")]
+type HttpsConnector = hyper_rustls::HttpsConnector<ProxyConnector>;
+#[cfg(feature = "rust-native-tls")]
+use hyper_tls;
+#[cfg(feature = "rust-native-tls")]
+type HttpsConnector = hyper_tls::HttpsConnector<ProxyConnector>;
+#[cfg(feature = "rustls")]
+type Certificate = rustls_crate::Certificate;
+#[cfg(feature = "rust-native-tls")]
+type Certificate = native_tls::Certificate;
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use http::{Request, Response};
+use hyper::{Body, Client};
+
+pub use dns::Resolve;
+pub use hyper::body::Bytes;
+pub use proxy::Proxy;
+#[cfg(unix)]
+pub use unix::UnixTransport;
+
+use crate::error::Error;
+use dns::Resolver;
+use proxy::ProxyConnector;
+
+/// Sends a request and returns the response. Request and response bodies are fully buffered.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(&self, req: Request<Bytes>) -> Result<Response<Bytes>, Error>;
+}
+
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    async fn send(&self, req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+        (**self).send(req).await
+    }
+}
+
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    async fn send(&self, req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+        (**self).send(req).await
+    }
+}
+
+/// The default transport, sending requests over HTTP(S) with `hyper`.
+#[derive(Clone)]
+pub struct HyperTransport {
+    client: Client<HttpsConnector>,
+    connector: ProxyConnector,
+}
+
+impl HyperTransport {
+    /// Creates a transport that uses the proxies set in the environment. Proxy variables that
+    /// can't be parsed are ignored; use [`Proxy::from_env`] to handle them.
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> HyperTransportBuilder {
+        HyperTransportBuilder::default()
+    }
+}
+
+/// Configures a [`HyperTransport`].
+#[derive(Clone, Default)]
+pub struct HyperTransportBuilder {
+    proxy: ProxySetting,
+    overrides: HashMap<String, Vec<IpAddr>>,
+    resolver: Option<Arc<dyn Resolve>>,
+    root_certificates: Vec<Certificate>,
+}
+
+#[derive(Debug, Clone, Default)]
+enum ProxySetting {
+    #[default]
+    Env,
+    Proxy(Box<Proxy>),
+    Direct,
+}
+
+impl HyperTransportBuilder {
+    /// Sends requests through the given proxy instead of the proxies set in the environment.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = ProxySetting::Proxy(Box::new(proxy));
+        self
+    }
+
+    /// Connects to the manager directly, ignoring the proxies set in the environment.
+    pub fn no_proxy(mut self) -> Self {
+        self.proxy = ProxySetting::Direct;
+        self
+    }
+
+    /// Connects to `addr` for requests to `host`, like curl's `--resolve`. Can be called more than
+    /// once for the same host to try several addresses in order. Overrides take precedence over
+    /// [`resolver`](Self::resolver).
+    ///
+    /// When requests to `host` go through a proxy, the proxy is asked to connect to `addr` instead
+    /// of resolving `host` itself. The hostname is still used for TLS and the `Host` header.
+    pub fn resolve(mut self, host: &str, addr: IpAddr) -> Self {
+        self.overrides
+            .entry(host.to_ascii_lowercase())
+            .or_default()
+            .push(addr);
+        self
+    }
+
+    /// Resolves hostnames with a custom resolver instead of the system resolver.
+    pub fn resolver<R: Resolve + 'static>(mut self, resolver: R) -> Self {
+        self.resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Trusts the CA certificates in a PEM bundle, in addition to the system's trusted roots.
+    /// Returns an error if the bundle doesn't contain any valid certificates.
+    pub fn add_root_certificates(mut self, pem: &[u8]) -> Result<Self, Error> {
+        let certs = parse_certificates(pem)?;
+        if certs.is_empty() {
+            return Err(Error::InvalidCertificate(
+                "no certificates found in PEM bundle".into(),
+            ));
+        }
+        self.root_certificates.extend(certs);
+        Ok(self)
+    }
+
+    pub fn build(self) -> HyperTransport {
+        let proxy = match self.proxy {
+            ProxySetting::Env => {
+                let proxy = Proxy::from_env();
+                #[cfg(feature = "tracing")]
+                if let Err(err) = &proxy {
+                    tracing::warn!(error = %err, "ignoring invalid proxy environment variable");
+                }
+                proxy.unwrap_or_default()
+            }
+            ProxySetting::Proxy(proxy) => Some(*proxy),
+            ProxySetting::Direct => None,
+        }
+        .map(Arc::new);
+
+        let resolver = Resolver::new(self.overrides, self.resolver);
+        let connector = ProxyConnector::new(resolver, proxy);
+        let https = https_connector(connector.clone(), &self.root_certificates);
+        HyperTransport {
+            client: Client::builder().build(https),
+            connector,
+        }
+    }
+}
+
+#[cfg(feature = "rustls")]
+fn parse_certificates(pem: &[u8]) -> Result<Vec<Certificate>, Error> {
+    let certs: Vec<_> = rustls_pemfile::certs(&mut &pem[..])
+        .map_err(|_| Error::InvalidCertificate("invalid PEM bundle".into()))?
+        .into_iter()
+        .map(rustls_crate::Certificate)
+        .collect();
+    for cert in &certs {
+        rustls_crate::RootCertStore::empty()
+            .add(cert)
+            .map_err(|err| Error::InvalidCertificate(err.to_string()))?;
+    }
+    Ok(certs)
+}
+
+#[cfg(feature = "rust-native-tls")]
+fn parse_certificates(pem: &[u8]) -> Result<Vec<Certificate>, Error> {
+    Certificate::stack_from_pem(pem).map_err(|err| Error::InvalidCertificate(err.to_string()))
+}
+
+#[cfg(feature = "rustls")]
+fn https_connector(http: ProxyConnector, roots: &[Certificate]) -> HttpsConnector {
+    // The same configuration as `hyper_rustls::HttpsConnector::new`.
+    let mut config = rustls_crate::ClientConfig::new();
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    config.root_store = match rustls_native_certs::load_native_certs() {
+        Ok(store) => store,
+        Err((Some(store), _)) => store,
+        Err((None, err)) => panic!("cannot access native cert store: {}", err),
+    };
+    for cert in roots {
+        // Certificates were validated when they were added.
+        let _ = config.root_store.add(cert);
+    }
+    config.ct_logs = Some(&ct_logs::LOGS);
+    (http, config).into()
+}
+
+#[cfg(feature = "rust-native-tls")]
+fn https_connector(http: ProxyConnector, roots: &[Certificate]) -> HttpsConnector {
+    let mut tls = native_tls::TlsConnector::builder();
+    for cert in roots {
+        tls.add_root_certificate(cert.clone());
+    }
+    // `HttpsConnector::new` panics the same way.
+    let tls = tls
+        .build()
+        .unwrap_or_else(|err| panic!("failed to create TLS connector: {}", err));
+    (http, tls.into()).into()
+}
+
+impl Default for HyperTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Transport for HyperTransport {
+    async fn send(&self, mut req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+        if let Some(auth) = self.connector.http_auth(req.uri()) {
+            req.headers_mut()
+                .insert(http::header::PROXY_AUTHORIZATION, auth);
+        }
+        send(&self.client, req).await
+    }
+}
+
+/// Sends a request with a hyper client and buffers the response body.
+async fn send<C>(client: &Client<C>, req: Request<Bytes>) -> Result<Response<Bytes>, Error>
+where
+    C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
+{
+    let res = client.request(req.map(Body::from)).await?;
+    let (parts, body) = res.into_parts();
+    let body = hyper::body::to_bytes(body).await?;
+    Ok(Response::from_parts(parts, body))
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::client::Executor;
+    use crate::Coder;
+
+    /// A transport that records requests and replies with a canned response.
+    pub(crate) struct MockTransport {
+        pub(crate) requests: Mutex<Vec<Request<Bytes>>>,
+        status: u16,
+        body: &'static str,
+    }
+
+    impl MockTransport {
+        pub(crate) fn new(status: u16, body: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                requests: Mutex::new(Vec::new()),
+                status,
+                body,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Transport for MockTransport {
+        async fn send(&self, req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+            self.requests.lock().unwrap().push(req);
+            Ok(Response::builder()
+                .status(self.status)
+                .header("Content-Type", "application/json")
+                .body(Bytes::from_static(self.body.as_bytes()))?)
+        }
+    }
+
+    /// A self-signed certificate for `coder.example.com`.
+    const CERT: &str = "-----BEGIN CERTIFICATE-----
+MIIBjzCCATWgAwIBAgIURgX6wA0U9e1r7YlP8T65Wf9poSIwCgYIKoZIzj0EAwIw
+HDEaMBgGA1UEAwwRY29kZXIuZXhhbXBsZS5jb20wIBcNMjYxMDE5MDYzNDQ0WhgP
+MjEyNjA5MjUwNjM0NDRaMBwxGjAYBgNVBAMMEWNvZGVyLmV4YW1wbGUuY29tMFkw
+EwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEDarKRl43sM+nR8mYLscedFLNCA4p7ENV
+QnXlbhzfzo5bt5UlAU1j12ySrkgX5dZW3oekvsQl4WfyeXhE08Ho06NTMFEwHQYD
+VR0OBBYEFEuPRac7UxYAHADe3Fw5ASeUx94HMB8GA1UdIwQYMBaAFEuPRac7UxYA
+HADe3Fw5ASeUx94HMA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDSAAwRQIg
+cB+4kdCXeZWQjaD+RS1nTvCM6VTqNA9mEiRhhKIqzMUCIQDjbE60SLmIe14IS19L
+Xb/69L/bLs2fp3wJwahKZ3iSjg==
+-----END CERTIFICATE-----
+";
+
+    #[test]
+    fn test_root_certificates() {
+        let bundle = format!("{}{}", CERT, CERT);
+        let b = HyperTransport::builder()
+            .add_root_certificates(bundle.as_bytes())
+            .unwrap();
+        assert_eq!(b.root_certificates.len(), 2);
+
+        for pem in &["", "not a certificate"] {
+            let res = HyperTransport::builder().add_root_certificates(pem.as_bytes());
+            assert!(
+                matches!(res, Err(Error::InvalidCertificate(_))),
+                "{:?}",
+                pem
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_transport() {
+        let t = MockTransport::new(200, r#"["default"]"#);
+        let c =
+            Coder::with_transport("https://coder.example.com".into(), "key", t.clone()).unwrap();
+
+        let res = c
+            .orgs()
+            .namespaces()
+            .execute()
+            .await
+            .expect("send request")
+            .response
+            .expect("api error returned");
+        assert_eq!(res, vec!["default".to_string()]);
+
+        let reqs = t.requests.lock().unwrap();
+        assert_eq!(reqs.len(), 1);
+        assert_eq!(reqs[0].method(), http::Method::GET);
+        assert_eq!(
+            reqs[0].uri(),
+            "https://coder.example.com/api/orgs/namespaces"
+        );
+        assert_eq!(reqs[0].headers()["Session-Token"], "key");
+    }
+
+    #[tokio::test]
+    async fn test_route_template() {
+        let t = MockTransport::new(200, "null");
+        let c =
+            Coder::with_transport("https://coder.example.com".into(), "key", t.clone()).unwrap();
+
+        let _ = c
+            .orgs()
+            .get("default")
+            .expect("build request")
+            .members()
+            .get("5e876cf4-10abe9b2e54eb609c5ec1870")
+            .expect("build request")
+            .execute()
+            .await;
+
+        let reqs = t.requests.lock().unwrap();
+        let route = reqs[0].extensions().get::<crate::client::Route>().unwrap();
+        assert_eq!(route.as_str(), "/orgs/:id/members/:user_id");
+    }
+
+    #[tokio::test]
+    async fn test_custom_transport_api_error() {
+        let t = MockTransport::new(404, r#"{"error":{"msg":"user not found"}}"#);
+        let c = Coder::with_transport("https://coder.example.com".into(), "key", t).unwrap();
+
+        let res = c
+            .users()
+            .get("5e876cf4-000000000000000000000000")
+            .expect("build request")
+            .execute()
+            .await
+            .expect("send request");
+        assert_eq!(res.status_code, 404);
+        assert_eq!(res.response.unwrap_err().error.msg, "user not found");
+    }
+}
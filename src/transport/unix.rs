@@ -0,0 +1,164 @@
+//! A transport for managers listening on a Unix domain socket.
+
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use http::{Request, Response, Uri};
+use hyper::client::connect::{Connected, Connection};
+use hyper::Client;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::UnixStream;
+use tower_service::Service;
+
+use super::{Bytes, Transport};
+use crate::error::Error;
+
+/// Sends requests over a Unix domain socket, without TLS. Used by
+/// [`Coder::unix`](crate::Coder::unix), and by [`Coder::new`](crate::Coder::new) for base URLs
+/// such as `unix:///var/run/coder.sock`.
+///
+/// Requests are sent to the same API routes as over the network, with `localhost` as the `Host`.
+#[derive(Clone)]
+pub struct UnixTransport {
+    client: Client<UnixConnector>,
+}
+
+impl UnixTransport {
+    /// Creates a transport that connects to the socket at `path`. The socket isn't opened until
+    /// the first request is sent.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let connector = UnixConnector {
+            path: path.into().into(),
+        };
+        Self {
+            client: Client::builder().build(connector),
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for UnixTransport {
+    async fn send(&self, req: Request<Bytes>) -> Result<Response<Bytes>, Error> {
+        super::send(&self.client, req).await
+    }
+}
+
+/// Connects to the socket regardless of the request URI.
+#[derive(Clone)]
+struct UnixConnector {
+    path: Arc<Path>,
+}
+
+impl Service<Uri> for UnixConnector {
+    type Response = UnixConnection;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<UnixConnection>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: Uri) -> Self::Future {
+        let path = Arc::clone(&self.path);
+        Box::pin(async move { Ok(UnixConnection(UnixStream::connect(&*path).await?)) })
+    }
+}
+
+struct UnixConnection(UnixStream);
+
+impl Connection for UnixConnection {
+    fn connected(&self) -> Connected {
+        Connected::new()
+    }
+}
+
+impl AsyncRead for UnixConnection {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for UnixConnection {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixListener;
+
+    use crate::{Coder, Error, Executor};
+
+    #[tokio::test]
+    async fn test_unix_socket() {
+        let path = std::env::temp_dir().join(format!("coder-rs-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut listener = UnixListener::bind(&path).unwrap();
+        let server = tokio::spawn(async move {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            let mut req = Vec::new();
+            let mut buf = [0; 1024];
+            while !req.ends_with(b"\r\n\r\n") {
+                let n = conn.read(&mut buf).await.unwrap();
+                req.extend_from_slice(&buf[..n]);
+            }
+            conn.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\n[\"default\"]")
+                .await
+                .unwrap();
+            String::from_utf8(req).unwrap()
+        });
+
+        let c = Coder::new(format!("unix://{}", path.display()), "key").unwrap();
+        let res = c.orgs().namespaces().execute().await.unwrap();
+        assert_eq!(res.response.unwrap(), vec!["default".to_string()]);
+
+        let req = server.await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(req.starts_with("GET /api/orgs/namespaces"), "{}", req);
+        assert!(req.contains("host: localhost\r\n"), "{}", req);
+        assert!(req.contains("session-token: key\r\n"), "{}", req);
+    }
+
+    #[test]
+    fn test_invalid_socket_url() {
+        for url in &["unix:", "unix:///", "unix://coder.sock"] {
+            let res = Coder::new(url.to_string(), "key");
+            assert!(matches!(res, Err(Error::InvalidBaseUrl { .. })), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_socket_url_needs_unix_transport() {
+        // Only `Coder::new` and `Coder::unix` send requests over a socket, so a custom transport
+        // can't be pointed at one by accident.
+        let t = crate::transport::HyperTransport::new();
+        let res = Coder::with_transport("unix:///var/run/coder.sock".into(), "key", t);
+        assert!(matches!(res, Err(Error::InvalidBaseUrl { .. })));
+
+        let c = Coder::unix("/var/run/coder.sock", "key").unwrap();
+        assert_eq!(c.url.as_str(), "http://localhost/api");
+    }
+}